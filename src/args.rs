@@ -41,7 +41,7 @@ pub enum MusicPlatformSrc {
         /// The client secret for the Youtube API application
         #[arg(long, env = "YTMUSIC_CLIENT_SECRET", conflicts_with = "headers")]
         client_secret: Option<String>,
-        /// Clear the cached ytmusic_oauth.json file
+        /// Clear the cached `ytmusic_oauth.json` file
         #[arg(long, requires = "client_id", requires = "client_secret")]
         clear_cache: bool,
         /// The destination music platform
@@ -55,7 +55,7 @@ pub enum MusicPlatformSrc {
         /// The client secret for the Spotify API application
         #[arg(long, env = "SPOTIFY_CLIENT_SECRET")]
         client_secret: String,
-        /// Clear the cached spotify_oauth.json file
+        /// Clear the cached `spotify_oauth.json` file
         #[arg(long)]
         clear_cache: bool,
         /// The destination music platform
@@ -69,7 +69,7 @@ pub enum MusicPlatformSrc {
         /// The client secret for the Tidal API application
         #[arg(long, env = "TIDAL_CLIENT_SECRET", default_value = TIDAL_DEFAULT_CLIENT_SECRET)]
         client_secret: String,
-        /// Clear the cached tidal_oauth.json file
+        /// Clear the cached `tidal_oauth.json` file
         #[arg(long)]
         clear_cache: bool,
         /// The destination music platform
@@ -98,9 +98,12 @@ pub enum MusicPlatformDst {
         /// The client secret for the Youtube API application
         #[arg(long, env = "YTMUSIC_CLIENT_SECRET", conflicts_with = "headers")]
         client_secret: Option<String>,
-        /// Clear the cached ytmusic_oauth.json file
+        /// Clear the cached `ytmusic_oauth.json` file
         #[arg(long, requires = "client_id", requires = "client_secret")]
         clear_cache: bool,
+        /// An additional destination music platform
+        #[command(subcommand)]
+        dst: Option<MusicPlatformNextDst>,
     },
     Spotify {
         /// The client ID for the Spotify API application
@@ -109,9 +112,12 @@ pub enum MusicPlatformDst {
         /// The client secret for the Spotify API application
         #[arg(long, env = "SPOTIFY_CLIENT_SECRET")]
         client_secret: String,
-        /// Clear the cached spotify_oauth.json file
+        /// Clear the cached `spotify_oauth.json` file
         #[arg(long)]
         clear_cache: bool,
+        /// An additional destination music platform
+        #[command(subcommand)]
+        dst: Option<MusicPlatformNextDst>,
     },
     Tidal {
         /// The client ID for the Tidal API application
@@ -120,9 +126,12 @@ pub enum MusicPlatformDst {
         #[arg(long, env = "TIDAL_CLIENT_SECRET", default_value = TIDAL_DEFAULT_CLIENT_SECRET)]
         /// The client secret for the Tidal API application
         client_secret: String,
-        /// Clear the cached tidal_oauth.json file
+        /// Clear the cached `tidal_oauth.json` file
         #[arg(long)]
         clear_cache: bool,
+        /// An additional destination music platform
+        #[command(subcommand)]
+        dst: Option<MusicPlatformNextDst>,
     },
     Export {
         /// The path to the file to export the playlists to
@@ -139,6 +148,83 @@ pub enum MusicPlatformDst {
     },
 }
 
+// INFO: Hack to support command chaining with clap
+// clap can't derive recursive subcommands, the remaining destinations are captured as raw
+// arguments and parsed again with `NextDstArgs`
+#[derive(Subcommand, Clone, Debug)]
+#[command(subcommand_value_name = "NEXT_DST_PLATFORM")]
+pub enum MusicPlatformNextDst {
+    YtMusic {
+        /// The path to the headers JSON file
+        #[arg(long)]
+        headers: Option<PathBuf>,
+        /// The client ID for the Youtube API application
+        #[arg(
+            long,
+            env = "YTMUSIC_CLIENT_ID",
+            conflicts_with = "headers",
+            requires = "client_secret"
+        )]
+        client_id: Option<String>,
+        /// The client secret for the Youtube API application
+        #[arg(long, env = "YTMUSIC_CLIENT_SECRET", conflicts_with = "headers")]
+        client_secret: Option<String>,
+        /// Clear the cached `ytmusic_oauth.json` file
+        #[arg(long, requires = "client_id", requires = "client_secret")]
+        clear_cache: bool,
+        /// Further destination music platforms, chained the same way
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "NEXT_DST_PLATFORM"
+        )]
+        next: Vec<String>,
+    },
+    Spotify {
+        /// The client ID for the Spotify API application
+        #[arg(long, env = "SPOTIFY_CLIENT_ID")]
+        client_id: String,
+        /// The client secret for the Spotify API application
+        #[arg(long, env = "SPOTIFY_CLIENT_SECRET")]
+        client_secret: String,
+        /// Clear the cached `spotify_oauth.json` file
+        #[arg(long)]
+        clear_cache: bool,
+        /// Further destination music platforms, chained the same way
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "NEXT_DST_PLATFORM"
+        )]
+        next: Vec<String>,
+    },
+    Tidal {
+        /// The client ID for the Tidal API application
+        #[arg(long, env = "TIDAL_CLIENT_ID", default_value = TIDAL_DEFAULT_CLIENT_ID)]
+        client_id: String,
+        /// The client secret for the Tidal API application
+        #[arg(long, env = "TIDAL_CLIENT_SECRET", default_value = TIDAL_DEFAULT_CLIENT_SECRET)]
+        client_secret: String,
+        /// Clear the cached `tidal_oauth.json` file
+        #[arg(long)]
+        clear_cache: bool,
+        /// Further destination music platforms, chained the same way
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "NEXT_DST_PLATFORM"
+        )]
+        next: Vec<String>,
+    },
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true, name = "NEXT_DST_PLATFORM")]
+pub struct NextDstArgs {
+    #[command(subcommand)]
+    pub dst: MusicPlatformNextDst,
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum LoggingLevel {
     /// Only log errors
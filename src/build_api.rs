@@ -1,13 +1,16 @@
 use std::path::Path;
 
 use async_trait::async_trait;
+use clap::Parser;
 use color_eyre::eyre::{Result, eyre};
 use sync_dis_boi::music_api::DynMusicApi;
 use sync_dis_boi::spotify::SpotifyApi;
 use sync_dis_boi::tidal::TidalApi;
 use sync_dis_boi::yt_music::YtMusicApi;
 
-use crate::args::{MusicPlatformDst, MusicPlatformSrc, RootArgs};
+use crate::args::{
+    MusicPlatformDst, MusicPlatformNextDst, MusicPlatformSrc, NextDstArgs, RootArgs,
+};
 
 #[async_trait]
 pub trait BuildApi {
@@ -99,6 +102,7 @@ macro_rules! impl_build_api {
 // related issue: https://github.com/clap-rs/clap/issues/2222
 impl_build_api!(MusicPlatformSrc);
 impl_build_api!(MusicPlatformDst);
+impl_build_api!(MusicPlatformNextDst);
 
 impl MusicPlatformSrc {
     pub fn get_dst(&self) -> &MusicPlatformDst {
@@ -107,3 +111,73 @@ impl MusicPlatformSrc {
         }
     }
 }
+
+impl MusicPlatformDst {
+    /// Additional destinations chained after this one, in order
+    pub fn get_next_dsts(&self) -> Vec<MusicPlatformNextDst> {
+        let mut next = match self {
+            Self::YtMusic { dst, .. } | Self::Spotify { dst, .. } | Self::Tidal { dst, .. } => {
+                dst.clone()
+            }
+            Self::Export { .. } | Self::Import { .. } => None,
+        };
+        let mut dsts = vec![];
+        while let Some(dst) = next {
+            next = dst.parse_next();
+            dsts.push(dst);
+        }
+        dsts
+    }
+}
+
+impl MusicPlatformNextDst {
+    /// Parse the destination chained after this one, exiting with the usage on errors
+    fn parse_next(&self) -> Option<Self> {
+        let (Self::YtMusic { next, .. } | Self::Spotify { next, .. } | Self::Tidal { next, .. }) =
+            self;
+        if next.is_empty() {
+            return None;
+        }
+        Some(NextDstArgs::parse_from(next).dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destination_chain() {
+        let args = RootArgs::parse_from([
+            "sync_dis_boi",
+            "spotify",
+            "--client-id",
+            "id",
+            "--client-secret",
+            "secret",
+            "tidal",
+            "yt-music",
+            "--headers",
+            "headers.json",
+            "spotify",
+            "--client-id",
+            "other",
+            "--client-secret",
+            "secret",
+            "--clear-cache",
+        ]);
+        let dsts = args.src.get_dst().get_next_dsts();
+        assert_eq!(dsts.len(), 2);
+        assert!(matches!(
+            &dsts[0],
+            MusicPlatformNextDst::YtMusic {
+                headers: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            &dsts[1],
+            MusicPlatformNextDst::Spotify { client_id, clear_cache: true, .. } if client_id == "other"
+        ));
+    }
+}
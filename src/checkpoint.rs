@@ -0,0 +1,278 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::ConfigArgs;
+use crate::matcher::{MatchPath, MatchThresholds};
+use crate::music_api::{MusicApiType, Song};
+
+/// Progress of a synchronization run, saved to the state file so an interrupted run can
+/// be resumed
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(default)]
+struct CheckpointState {
+    /// Completed steps, e.g. `0:tidal:playlist:<source id>` or `1:tidal:likes`
+    completed: HashSet<String>,
+    /// Search results keyed by destination and source song, None when no match was
+    /// found
+    resolved: HashMap<String, Option<(Song, MatchPath)>>,
+    /// Thresholds the songs were resolved with, the search results are discarded when
+    /// they change
+    thresholds: Option<MatchThresholds>,
+}
+
+impl CheckpointState {
+    /// Discard the search results, along with the completed likes steps: the likes mirror
+    /// finds the counterparts of the source likes among them
+    fn discard_resolved(&mut self) {
+        self.resolved.clear();
+        let likes_suffix = format!(":{}", Checkpoint::LIKES_STEP);
+        self.completed.retain(|step| !step.ends_with(&likes_suffix));
+    }
+}
+
+pub struct Checkpoint {
+    path: Option<PathBuf>,
+    state: CheckpointState,
+    /// Steps completed by the resumed run, skipped by this one
+    resumed: HashSet<String>,
+    /// Number of changes since the last save
+    unsaved: usize,
+}
+
+impl Checkpoint {
+    const SAVE_INTERVAL: usize = 50;
+    const LIKES_STEP: &'static str = "likes";
+
+    /// Checkpoint kept in memory only
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            state: CheckpointState::default(),
+            resumed: HashSet::new(),
+            unsaved: 0,
+        }
+    }
+
+    pub fn from_config(config: &ConfigArgs) -> Result<Self> {
+        let Some(path) = config.state_file.clone() else {
+            return Ok(Self::disabled());
+        };
+        let thresholds = MatchThresholds::from_config(config);
+        let mut state = CheckpointState::default();
+        if config.resume {
+            if path.exists() {
+                info!("resuming the synchronization from {}", path.display());
+                state = serde_json::from_reader(std::fs::File::open(&path)?)?;
+                if state.thresholds != Some(thresholds) {
+                    info!("matching settings changed, searching the songs again");
+                    state.discard_resolved();
+                }
+            } else {
+                warn!("no synchronization to resume, starting from scratch");
+            }
+        }
+        state.thresholds = Some(thresholds);
+        Ok(Self {
+            path: Some(path),
+            resumed: state.completed.clone(),
+            state,
+            unsaved: 0,
+        })
+    }
+
+    /// Whether the progress is saved to a state file
+    pub fn is_persistent(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Destination prefix of the keys, the index in the destinations chain tells apart
+    /// the accounts of the same platform
+    fn destination(dst_index: usize, dst_type: &MusicApiType) -> String {
+        format!("{}:{}", dst_index, dst_type.short_name())
+    }
+
+    pub fn playlist_step(dst_index: usize, dst_type: &MusicApiType, playlist_id: &str) -> String {
+        format!(
+            "{}:playlist:{}",
+            Self::destination(dst_index, dst_type),
+            playlist_id
+        )
+    }
+
+    pub fn likes_step(dst_index: usize, dst_type: &MusicApiType) -> String {
+        format!(
+            "{}:{}",
+            Self::destination(dst_index, dst_type),
+            Self::LIKES_STEP
+        )
+    }
+
+    pub fn albums_step(dst_index: usize, dst_type: &MusicApiType) -> String {
+        format!("{}:albums", Self::destination(dst_index, dst_type))
+    }
+
+    pub fn artists_step(dst_index: usize, dst_type: &MusicApiType) -> String {
+        format!("{}:artists", Self::destination(dst_index, dst_type))
+    }
+
+    fn song_key(src_song: &Song, dst_index: usize, dst_type: &MusicApiType) -> String {
+        format!(
+            "{}:{}:{}",
+            Self::destination(dst_index, dst_type),
+            src_song.source.short_name(),
+            src_song.id
+        )
+    }
+
+    /// Whether the step was completed by the resumed run, or earlier by this one
+    pub fn is_completed(&self, step: &str) -> bool {
+        self.state.completed.contains(step)
+    }
+
+    /// Whether the step was completed by the resumed run and can be skipped
+    pub fn was_completed(&self, step: &str) -> bool {
+        self.resumed.contains(step)
+    }
+
+    pub fn complete(&mut self, step: &str) -> Result<()> {
+        self.state.completed.insert(step.to_string());
+        self.save()
+    }
+
+    /// Search result of a previous attempt, None if the song was not searched yet
+    pub fn resolved(
+        &self,
+        src_song: &Song,
+        dst_index: usize,
+        dst_type: &MusicApiType,
+    ) -> Option<&Option<(Song, MatchPath)>> {
+        self.state
+            .resolved
+            .get(&Self::song_key(src_song, dst_index, dst_type))
+    }
+
+    pub fn resolve(
+        &mut self,
+        src_song: &Song,
+        dst_index: usize,
+        dst_type: &MusicApiType,
+        dst_song: Option<(Song, MatchPath)>,
+    ) -> Result<()> {
+        self.state
+            .resolved
+            .insert(Self::song_key(src_song, dst_index, dst_type), dst_song);
+        self.unsaved += 1;
+        if self.unsaved >= Self::SAVE_INTERVAL {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(path) = &self.path {
+            std::fs::write(path, serde_json::to_string(&self.state)?)?;
+            self.unsaved = 0;
+        }
+        Ok(())
+    }
+
+    /// Remove the state file once the whole synchronization succeeded
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(path) = &self.path
+            && path.exists()
+        {
+            std::fs::remove_file(path)?;
+        }
+        self.state = CheckpointState::default();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_steps() {
+        let song = Song::test(MusicApiType::Spotify, "abc", "Song");
+        let mut checkpoint = Checkpoint::disabled();
+        let step = Checkpoint::playlist_step(0, &MusicApiType::Tidal, "rock-id");
+        assert!(!checkpoint.is_completed(&step));
+        assert!(
+            checkpoint
+                .resolved(&song, 0, &MusicApiType::Tidal)
+                .is_none()
+        );
+
+        checkpoint
+            .resolve(
+                &song,
+                0,
+                &MusicApiType::Tidal,
+                Some((song.clone(), MatchPath::Isrc)),
+            )
+            .unwrap();
+        checkpoint
+            .resolve(&song, 1, &MusicApiType::YtMusic, None)
+            .unwrap();
+        assert!(matches!(
+            checkpoint.resolved(&song, 0, &MusicApiType::Tidal),
+            Some(Some((_, MatchPath::Isrc)))
+        ));
+        assert!(matches!(
+            checkpoint.resolved(&song, 1, &MusicApiType::YtMusic),
+            Some(None)
+        ));
+
+        checkpoint.complete(&step).unwrap();
+        assert!(checkpoint.is_completed(&step));
+        // only the steps of the resumed run are skipped
+        assert!(!checkpoint.was_completed(&step));
+    }
+
+    #[test]
+    fn test_checkpoint_same_platform_destinations() {
+        let song = Song::test(MusicApiType::Spotify, "abc", "Song");
+        let mut checkpoint = Checkpoint::disabled();
+        checkpoint
+            .complete(&Checkpoint::likes_step(0, &MusicApiType::Tidal))
+            .unwrap();
+        checkpoint
+            .resolve(&song, 0, &MusicApiType::Tidal, None)
+            .unwrap();
+        // a second Tidal account chained after the first one
+        assert!(!checkpoint.is_completed(&Checkpoint::likes_step(1, &MusicApiType::Tidal)));
+        assert!(
+            checkpoint
+                .resolved(&song, 1, &MusicApiType::Tidal)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_discard_resolved() {
+        let song = Song::test(MusicApiType::Spotify, "abc", "Song");
+        let likes_step = Checkpoint::likes_step(0, &MusicApiType::Tidal);
+        let playlist_step = Checkpoint::playlist_step(0, &MusicApiType::Tidal, "rock-id");
+        let mut checkpoint = Checkpoint::disabled();
+        checkpoint.complete(&likes_step).unwrap();
+        checkpoint.complete(&playlist_step).unwrap();
+        checkpoint
+            .resolve(&song, 0, &MusicApiType::Tidal, None)
+            .unwrap();
+
+        checkpoint.state.discard_resolved();
+        assert!(
+            checkpoint
+                .resolved(&song, 0, &MusicApiType::Tidal)
+                .is_none()
+        );
+        // the likes are synchronized again so that the mirror knows their counterparts
+        assert!(!checkpoint.is_completed(&likes_step));
+        assert!(checkpoint.is_completed(&playlist_step));
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use clap::ValueEnum;
+use color_eyre::eyre::{OptionExt, Result, eyre};
+use serde::Deserialize;
+use strsim::normalized_levenshtein;
+use tracing::{debug, info, warn};
+
+use crate::ConfigArgs;
+use crate::music_api::{DynMusicApi, MusicApiType, Song};
+use crate::utils::{RateLimiter, clean_isrc, client_builder, debug_response_json};
+
+/// Source of the ISRC codes of the songs lacking one
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IsrcResolverKind {
+    /// `MusicBrainz` recordings database
+    Musicbrainz,
+    /// First destination platform exposing ISRC codes (Spotify or Tidal)
+    Destination,
+}
+
+#[async_trait]
+pub trait IsrcResolver: Sync {
+    async fn resolve_isrc(&self, song: &Song) -> Result<Option<String>>;
+}
+
+pub fn build_resolver<'a>(
+    kind: IsrcResolverKind,
+    dst_apis: &'a [DynMusicApi],
+    config: &ConfigArgs,
+) -> Result<Box<dyn IsrcResolver + 'a>> {
+    match kind {
+        IsrcResolverKind::Musicbrainz => Ok(Box::new(MusicBrainzResolver::new(
+            config,
+            dst_apis.first(),
+        )?)),
+        IsrcResolverKind::Destination => {
+            let api = dst_apis
+                .iter()
+                .find(|api| api.api_type() != MusicApiType::YtMusic)
+                .ok_or_eyre("no destination platform exposing ISRC codes")?;
+            Ok(Box::new(ApiIsrcResolver { api }))
+        }
+    }
+}
+
+/// Resolves ISRC codes by searching the songs on an ISRC-capable platform
+pub struct ApiIsrcResolver<'a> {
+    api: &'a DynMusicApi,
+}
+
+#[async_trait]
+impl IsrcResolver for ApiIsrcResolver<'_> {
+    async fn resolve_isrc(&self, song: &Song) -> Result<Option<String>> {
+        let res_song = self.api.search_song(song).await?;
+        Ok(res_song.and_then(|s| s.isrc))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MusicBrainzSearchResponse {
+    pub recordings: Vec<MusicBrainzRecording>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MusicBrainzRecording {
+    pub title: String,
+    pub score: u32,
+    pub length: Option<usize>,
+    pub isrcs: Option<Vec<String>>,
+}
+
+pub struct MusicBrainzResolver<'a> {
+    client: reqwest::Client,
+    config: ConfigArgs,
+    rate_limiter: RateLimiter,
+    /// Platform on which the ISRC codes of the recordings are looked up, to pick one it
+    /// knows
+    dst_api: Option<&'a DynMusicApi>,
+}
+
+impl<'a> MusicBrainzResolver<'a> {
+    const SEARCH_URL: &'static str = "https://musicbrainz.org/ws/2/recording";
+    const USER_AGENT: &'static str = concat!(
+        "SyncDisBoi/",
+        env!("CARGO_PKG_VERSION"),
+        " ( https://github.com/SilentVoid13/SyncDisBoi )"
+    );
+    // NOTE: MusicBrainz allows an average of one request per second
+    const RATE_LIMIT: Duration = Duration::from_secs(1);
+    const MIN_SCORE: u32 = 90;
+    const RES_DEBUG_FILENAME: &'static str = "musicbrainz";
+
+    pub fn new(config: &ConfigArgs, dst_api: Option<&'a DynMusicApi>) -> Result<Self> {
+        let client = client_builder(config)?.user_agent(Self::USER_AGENT);
+        Ok(Self {
+            client: client.build()?,
+            config: config.clone(),
+            rate_limiter: RateLimiter::new(Self::RATE_LIMIT),
+            dst_api,
+        })
+    }
+
+    /// Lucene query of the exact cleaned song name, e.g. without the featured artists
+    /// and the version descriptors, and of its main artist
+    fn build_query(song: &Song) -> Result<String> {
+        // quotes would break the lucene query
+        let escape = |s: &str| s.replace('"', "");
+        let mut query = format!("recording:\"{}\"", escape(&song.clean_name()));
+        if let Some(artist) = song.artists.first() {
+            write!(query, " AND artist:\"{}\"", escape(&artist.clean_name()))?;
+        }
+        Ok(query)
+    }
+
+    /// ISRCs of the confident recordings with a close title and duration, in the
+    /// recordings order
+    fn select_isrcs(song: &Song, recordings: Vec<MusicBrainzRecording>) -> Vec<String> {
+        let name = song.clean_name();
+        let mut isrcs: Vec<String> = recordings
+            .into_iter()
+            .filter(|r| r.score >= Self::MIN_SCORE)
+            .filter(|r| {
+                r.length
+                    .is_none_or(|l| (l / 1000).abs_diff(song.duration_ms / 1000) <= 2)
+            })
+            .filter(|r| {
+                let title = Song {
+                    name: r.title.clone(),
+                    ..song.clone()
+                };
+                normalized_levenshtein(&name, &title.clean_name()) >= 0.8
+            })
+            .flat_map(|r| r.isrcs.unwrap_or_default())
+            .filter_map(|isrc| clean_isrc(Some(isrc)))
+            .collect();
+        let mut seen = HashSet::new();
+        isrcs.retain(|isrc| seen.insert(isrc.clone()));
+        isrcs
+    }
+
+    /// First ISRC known by the destination, the recordings can have several ones, e.g.
+    /// for the regional releases
+    async fn pick_isrc(&self, song: &Song, isrcs: Vec<String>) -> Result<Option<String>> {
+        if let Some(dst_api) = self.dst_api
+            && isrcs.len() > 1
+        {
+            for isrc in &isrcs {
+                if dst_api.search_isrc(song, isrc).await?.is_some() {
+                    return Ok(Some(isrc.clone()));
+                }
+            }
+            debug!(
+                "no ISRC known by the destination, keeping the first one: {}",
+                song
+            );
+        }
+        Ok(isrcs.into_iter().next())
+    }
+}
+
+#[async_trait]
+impl IsrcResolver for MusicBrainzResolver<'_> {
+    async fn resolve_isrc(&self, song: &Song) -> Result<Option<String>> {
+        let query = Self::build_query(song)?;
+        self.rate_limiter.wait().await;
+        let res = self
+            .client
+            .get(Self::SEARCH_URL)
+            .query(&[("query", query.as_str()), ("fmt", "json"), ("limit", "5")])
+            .send()
+            .await?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(eyre!("Invalid HTTP status: {}", status));
+        }
+        let res: MusicBrainzSearchResponse =
+            debug_response_json(&self.config, res, Self::RES_DEBUG_FILENAME).await?;
+        let isrcs = Self::select_isrcs(song, res.recordings);
+        self.pick_isrc(song, isrcs).await
+    }
+}
+
+/// Resolved ISRC codes, None when no ISRC was found, keyed by source platform and song ID
+#[derive(Default)]
+pub struct IsrcCache(HashMap<String, Option<String>>);
+
+impl IsrcCache {
+    pub fn load_opt(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) if path.exists() => {
+                Ok(Self(serde_json::from_reader(std::fs::File::open(path)?)?))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn save_opt(&self, path: Option<&Path>) -> Result<()> {
+        if let Some(path) = path {
+            std::fs::write(path, serde_json::to_string(&self.0)?)?;
+        }
+        Ok(())
+    }
+
+    fn key(song: &Song) -> String {
+        format!("{}:{}", song.source.short_name(), song.id)
+    }
+}
+
+/// Fill the ISRC codes of the songs lacking one, the results are cached
+pub async fn enrich_isrcs(
+    songs: &mut [&mut Song],
+    resolver: &dyn IsrcResolver,
+    config: &ConfigArgs,
+) -> Result<()> {
+    let cache_path = config.isrc_cache.as_deref();
+    let mut cache = IsrcCache::load_opt(cache_path)?;
+
+    let mut found = 0;
+    let mut requests = 0;
+    let missing = songs.iter().filter(|s| s.isrc.is_none()).count();
+    info!("resolving ISRC codes of {} songs...", missing);
+    for song in songs.iter_mut().filter(|s| s.isrc.is_none()) {
+        let key = IsrcCache::key(song);
+        let isrc = if let Some(isrc) = cache.0.get(&key) {
+            isrc.clone()
+        } else {
+            let isrc = match resolver.resolve_isrc(song).await {
+                Ok(isrc) => isrc,
+                Err(e) => {
+                    // not cached, it will be retried on the next run
+                    warn!("failed to resolve ISRC of song \"{}\": {}", song, e);
+                    continue;
+                }
+            };
+            cache.0.insert(key, isrc.clone());
+            requests += 1;
+            if requests % 50 == 0 {
+                cache.save_opt(cache_path)?;
+            }
+            isrc
+        };
+        if let Some(isrc) = isrc {
+            debug!("resolved ISRC {} of song: {}", isrc, song);
+            song.isrc = Some(isrc);
+            found += 1;
+        }
+    }
+    cache.save_opt(cache_path)?;
+    info!("resolved {}/{} ISRC codes", found, missing);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_musicbrainz_select_isrc() {
+        let song = Song::test(
+            MusicApiType::YtMusic,
+            "5NV6Rdv1a3I",
+            "Get Lucky (feat. Pharrell Williams)",
+        )
+        .with_duration(369);
+        let res: MusicBrainzSearchResponse = serde_json::from_str(
+            r#"{
+                "recordings": [
+                    { "title": "Get Lucky", "score": 70, "length": 369000, "isrcs": ["USQX91300101"] },
+                    { "title": "Get Lucky", "score": 100, "length": 248000, "isrcs": ["USQX91300102"] },
+                    { "title": "Get Lucky", "score": 100, "length": 369626 },
+                    { "title": "Get Lucky", "score": 98, "length": 368000, "isrcs": ["usqx9-13-00108", "GBDUW1300109"] },
+                    { "title": "Get Lucky", "score": 95, "length": 369000, "isrcs": ["USQX91300108"] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let isrcs = MusicBrainzResolver::select_isrcs(&song, res.recordings);
+        assert_eq!(isrcs, ["USQX91300108", "GBDUW1300109"]);
+    }
+
+    #[test]
+    fn test_musicbrainz_build_query() {
+        let song = Song::test(
+            MusicApiType::YtMusic,
+            "5NV6Rdv1a3I",
+            "Get Lucky (feat. Pharrell Williams) - Radio Edit",
+        )
+        .with_artist("Daft Punk");
+        let query = MusicBrainzResolver::build_query(&song).unwrap();
+        assert_eq!(query, r#"recording:"get lucky" AND artist:"daft punk""#);
+
+        let song = Song::test(MusicApiType::YtMusic, "id", r#"The "Heroes" Song"#);
+        let query = MusicBrainzResolver::build_query(&song).unwrap();
+        assert_eq!(query, r#"recording:"the heroes song""#);
+    }
+}
@@ -1,21 +1,70 @@
 use std::path::Path;
+use std::time::Instant;
 
 use color_eyre::eyre::Result;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::ConfigArgs;
-use crate::music_api::{DynMusicApi, Playlist};
-use crate::sync::synchronize_playlists;
+use crate::checkpoint::Checkpoint;
+use crate::enrich::{build_resolver, enrich_isrcs};
+use crate::mapping::Mapping;
+use crate::matcher::DefaultMatcher;
+use crate::music_api::{DynMusicApi, Playlist, Song};
+use crate::report::DestinationReport;
+use crate::sync::{SyncContext, synchronize_likes, synchronize_playlists};
 
 pub async fn import(src_json: &Path, dst_api: DynMusicApi, config: ConfigArgs) -> Result<()> {
-    let src_playlists: Vec<Playlist> = serde_json::from_reader(std::fs::File::open(src_json)?)?;
+    let mut src_playlists: Vec<Playlist> = serde_json::from_reader(std::fs::File::open(src_json)?)?;
+    let mapping = Mapping::load_opt(config.mapping.as_deref())?;
+    let matcher = DefaultMatcher::from_config(&config);
+    mapping.apply_merges(&mut src_playlists, &matcher)?;
+    if mapping.likes_playlist.is_some() {
+        warn!("exported playlists do not include likes, ignoring the likes playlist mapping");
+    }
+    let started = Instant::now();
+    let mut ctx = SyncContext::from_config(&config, None)?;
+    if let Some(kind) = config.isrc_resolver {
+        let dst_apis = std::slice::from_ref(&dst_api);
+        let resolver = build_resolver(kind, dst_apis, &config)?;
+        let mut songs: Vec<&mut Song> = src_playlists
+            .iter_mut()
+            .flat_map(|p| p.songs.iter_mut())
+            .collect();
+        enrich_isrcs(&mut songs, resolver.as_ref(), &config).await?;
+    }
+    let src_likes = mapping.take_likes_playlists(&mut src_playlists, &matcher);
 
     info!("importing playlists...");
-    synchronize_playlists(src_playlists, &dst_api, &config).await?;
+    let mut summary =
+        synchronize_playlists(src_playlists, &dst_api, &mapping, &mut ctx, &config).await?;
+    let likes_step = Checkpoint::likes_step(ctx.destination, &dst_api.api_type());
+    if !src_likes.is_empty() && !ctx.checkpoint.was_completed(&likes_step) {
+        info!("importing likes...");
+        let likes_summary = synchronize_likes(&src_likes, &dst_api, &mut ctx, &config).await?;
+        if likes_summary.failures == 0 {
+            ctx.checkpoint.complete(&likes_step)?;
+        }
+        summary.merge(likes_summary);
+    }
+    info!("import summary: {}", summary);
+
+    if let Some(path) = &config.report {
+        let duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        ctx.report.destinations.push(DestinationReport {
+            index: ctx.destination,
+            platform: dst_api.api_type(),
+            summary,
+            duration_ms,
+        });
+        ctx.report.duration_ms = duration_ms;
+        ctx.report.save(path, config.report_format)?;
+        info!("import report saved to {}", path.display());
+    }
+
+    ctx.finish(summary.failures)?;
     info!(
         "successfully imported playlists to {:?}",
         dst_api.api_type()
     );
-
     Ok(())
 }
@@ -1,14 +1,28 @@
+pub mod checkpoint;
+pub mod enrich;
 pub mod export;
 pub mod import;
+pub mod mapping;
+pub mod matcher;
 pub mod music_api;
+pub mod overrides;
+pub mod report;
+pub mod review;
+pub mod search_cache;
 pub mod spotify;
 pub mod sync;
 pub mod tidal;
 pub mod utils;
 pub mod yt_music;
 
+use std::path::PathBuf;
+
 use clap::Parser;
 
+use crate::enrich::IsrcResolverKind;
+use crate::matcher::MatchProfile;
+use crate::report::ReportFormat;
+
 // TODO: I don't really like depending on clap for the library,
 // but it's the easiest way to share a configuration structure with the bin
 #[derive(Parser, Debug, Clone)]
@@ -26,12 +40,135 @@ pub struct ConfigArgs {
     #[arg(long, default_value = "false")]
     pub sync_likes: bool,
 
+    /// Mirror the source likes: the destination likes without a source counterpart are
+    /// removed
+    #[arg(
+        long,
+        default_value = "false",
+        requires = "sync_likes",
+        conflicts_with = "like_all"
+    )]
+    pub mirror_likes: bool,
+
+    /// Only list the destination likes that would be removed by --mirror-likes
+    #[arg(long, default_value = "false", requires = "mirror_likes")]
+    pub mirror_dry_run: bool,
+
+    /// Maximum number of likes removed by --mirror-likes, above which nothing is removed
+    #[arg(long, default_value = "100")]
+    pub max_like_removals: usize,
+
+    /// Sync followed artists from the source platform to the destination platform.
+    #[arg(long, default_value = "false")]
+    pub sync_artists: bool,
+
+    /// Sync saved albums from the source platform to the destination platform.
+    #[arg(long, default_value = "false")]
+    pub sync_albums: bool,
+
     /// Allow the synchronization between platforms with different countries.
     /// Be aware that this can lead to invalid sync results, as some songs will
     /// have different ISRC codes.
     #[arg(long, default_value = "false")]
     pub diff_country: bool,
 
+    /// JSON mapping file describing how source playlists are mapped to destination
+    /// playlists, e.g. to merge several source playlists into one
+    #[arg(long)]
+    pub mapping: Option<PathBuf>,
+
+    /// JSON file of manual matches, forcing the destination song of a source song or
+    /// skipping it
+    #[arg(long)]
+    pub overrides: Option<PathBuf>,
+
+    /// Interactively review the missing and unconfident matches, the decisions are
+    /// saved to the overrides file
+    #[arg(long, default_value = "false", requires = "overrides")]
+    pub interactive: bool,
+
+    /// Matches with a lower confidence (0 to 1) are reviewed in interactive mode
+    #[arg(long, default_value = "0.9")]
+    pub review_confidence: f64,
+
+    /// Fill the missing ISRC codes of the source songs before searching them, e.g. for
+    /// Youtube Music
+    #[arg(long, value_enum)]
+    pub isrc_resolver: Option<IsrcResolverKind>,
+
+    /// Cache file of the resolved ISRC codes, defaults to `isrc_cache.json` in the
+    /// config directory
+    #[arg(long)]
+    pub isrc_cache: Option<PathBuf>,
+
+    /// Cache file of the search results, defaults to `search_cache.json` in the config
+    /// directory
+    #[arg(long)]
+    pub search_cache: Option<PathBuf>,
+
+    /// Number of days after which songs that were not found are searched again
+    #[arg(long, default_value = "7")]
+    pub search_cache_ttl: u64,
+
+    /// Search every song again, without reading or updating the search cache
+    #[arg(long, default_value = "false")]
+    pub no_search_cache: bool,
+
+    /// Clear the search cache before synchronizing
+    #[arg(long, default_value = "false", conflicts_with = "no_search_cache")]
+    pub clear_search_cache: bool,
+
+    /// Number of search results compared to the source song for each search query
+    #[arg(long, default_value = "5")]
+    pub search_candidates: usize,
+
+    /// Continue the interrupted synchronization saved in the state file, without
+    /// redoing the completed playlists and searches
+    #[arg(long, default_value = "false")]
+    pub resume: bool,
+
+    /// State file saving the progress of the synchronization, defaults to
+    /// `sync_state.json` in the config directory
+    #[arg(long)]
+    pub state_file: Option<PathBuf>,
+
+    /// Write a report of the synchronization to this file
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// Format of the report, guessed from the file extension by default
+    #[arg(long, value_enum, requires = "report")]
+    pub report_format: Option<ReportFormat>,
+
+    /// Preset of song matching thresholds
+    #[arg(long, value_enum, default_value_t = MatchProfile::Balanced)]
+    pub match_profile: MatchProfile,
+
+    /// Minimum song name resemblance score (0 to 1), overrides the profile
+    #[arg(long)]
+    pub name_threshold: Option<f64>,
+
+    /// Minimum album name resemblance score (0 to 1), overrides the profile
+    #[arg(long)]
+    pub album_threshold: Option<f64>,
+
+    /// Minimum ratio of common artists (0 to 1), overrides the profile
+    #[arg(long)]
+    pub artist_threshold: Option<f64>,
+
+    /// Maximum song duration difference in seconds, overrides the profile
+    #[arg(long)]
+    pub duration_tolerance: Option<usize>,
+
+    /// Never match songs lacking artists metadata
+    #[arg(long, default_value = "false")]
+    pub require_artist: bool,
+
+    /// Compare the names transliterated to ASCII, e.g. for platforms romanizing the
+    /// non-latin titles
+    #[arg(long, default_value = "false")]
+    pub transliterate: bool,
+
     /// Proxy to use for all requests in the format http://<ip>:<port>
     #[arg(long)]
     pub proxy: Option<String>,
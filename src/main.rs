@@ -9,16 +9,19 @@ use clap::Parser;
 use color_eyre::eyre::{Result, eyre};
 use sync_dis_boi::export::export;
 use sync_dis_boi::import::import;
-use sync_dis_boi::sync::synchronize;
-use tracing::{Level, debug, info};
+use sync_dis_boi::sync::{PartialSyncError, synchronize};
+use tracing::{Level, debug, error, info};
 use tracing_subscriber::filter::Targets;
 use tracing_subscriber::prelude::*;
 
+/// Exit code when some playlists, songs, likes or albums failed to synchronize
+const PARTIAL_FAILURE_EXIT_CODE: i32 = 2;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
     // CLI arguments
-    let args = RootArgs::parse();
+    let mut args = RootArgs::parse();
 
     // Setup logging
     const CRATE_NAME: &str = env!("CARGO_PKG_NAME");
@@ -39,6 +42,15 @@ async fn main() -> Result<()> {
         info!("creating SyncDisBoi config directory: {:?}", config_dir);
         std::fs::create_dir_all(&config_dir)?;
     }
+    if args.config.isrc_cache.is_none() {
+        args.config.isrc_cache = Some(config_dir.join("isrc_cache.json"));
+    }
+    if args.config.state_file.is_none() {
+        args.config.state_file = Some(config_dir.join("sync_state.json"));
+    }
+    if args.config.search_cache.is_none() {
+        args.config.search_cache = Some(config_dir.join("search_cache.json"));
+    }
 
     if args.config.debug {
         let debug_dir = Path::new("debug");
@@ -49,18 +61,24 @@ async fn main() -> Result<()> {
     }
 
     let src_api = args.src.parse(&args, &config_dir).await?;
-    match args.src.get_dst() {
-        MusicPlatformDst::Export { output, minify } => {
-            export(src_api, output, *minify).await?;
+    let res = match args.src.get_dst() {
+        MusicPlatformDst::Export { output, minify } => export(src_api, output, *minify).await,
+        MusicPlatformDst::Import { input } => import(input, src_api, args.config).await,
+        dst => {
+            let mut dst_apis = vec![dst.parse(&args, &config_dir).await?];
+            for next_dst in dst.get_next_dsts() {
+                dst_apis.push(next_dst.parse(&args, &config_dir).await?);
+            }
+            synchronize(src_api, dst_apis, args.config).await
         }
-        MusicPlatformDst::Import { input } => {
-            import(input, src_api, args.config).await?;
-        }
-        _ => {
-            let dst_api = args.src.get_dst().parse(&args, &config_dir).await?;
-            synchronize(src_api, dst_api, args.config).await?;
-        }
-    }
+    };
 
-    Ok(())
+    // a partial failure is already detailed by the summary, no need for a backtrace
+    if let Err(e) = &res
+        && let Some(partial) = e.downcast_ref::<PartialSyncError>()
+    {
+        error!("{}", partial);
+        std::process::exit(PARTIAL_FAILURE_EXIT_CODE);
+    }
+    res
 }
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::matcher::Matcher;
+use crate::music_api::{Playlist, Song};
+
+/// User-provided mapping configuration, loaded from a JSON file
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Mapping {
+    /// Destination playlists aggregated from several source playlists
+    pub merge: Vec<MergedPlaylist>,
+    /// Source playlist name or ID to destination playlist name
+    pub rename: HashMap<String, String>,
+    /// Rules applied in order to the destination playlist names not found in `rename`
+    pub rules: Vec<RenameRule>,
+    /// Destination playlist receiving the source likes
+    pub likes_playlist: Option<String>,
+    /// Source playlists names or IDs synchronized to the destination likes instead of a
+    /// playlist
+    pub to_likes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameRule {
+    Prefix(String),
+    Suffix(String),
+    Replace {
+        pattern: String,
+        replacement: String,
+    },
+}
+
+impl RenameRule {
+    pub fn apply(&self, name: &str) -> Result<String> {
+        let name = match self {
+            Self::Prefix(prefix) => format!("{}{}", prefix, name),
+            Self::Suffix(suffix) => format!("{}{}", name, suffix),
+            Self::Replace {
+                pattern,
+                replacement,
+            } => Regex::new(pattern)?
+                .replace_all(name, replacement.as_str())
+                .to_string(),
+        };
+        Ok(name)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MergedPlaylist {
+    /// Name of the destination playlist
+    pub name: String,
+    pub sources: Vec<MergeSource>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MergeSource {
+    /// Name or ID of the source playlist
+    pub playlist: String,
+    /// Exported JSON file to read the playlist from, allowing to merge playlists from
+    /// other platforms. Defaults to the source platform playlists.
+    pub file: Option<PathBuf>,
+}
+
+impl Mapping {
+    const MERGE_ID_PREFIX: &'static str = "merge:";
+    /// ID of the playlist made of the source likes
+    pub const LIKES_ID: &'static str = "likes:";
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let mapping: Self = serde_json::from_reader(std::fs::File::open(path)?)?;
+        // fail early on invalid regexes
+        for rule in &mapping.rules {
+            rule.apply("")?;
+        }
+        Ok(mapping)
+    }
+
+    pub fn load_opt(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Build the merged playlists and append them to the source playlists
+    pub fn apply_merges(
+        &self,
+        src_playlists: &mut Vec<Playlist>,
+        matcher: &dyn Matcher,
+    ) -> Result<()> {
+        let mut merged_playlists = vec![];
+        for merged in &self.merge {
+            let mut songs: Vec<Song> = vec![];
+            for source in &merged.sources {
+                let file_playlists: Vec<Playlist>;
+                let playlists = if let Some(file) = &source.file {
+                    file_playlists = serde_json::from_reader(std::fs::File::open(file)?)?;
+                    &file_playlists
+                } else {
+                    &*src_playlists
+                };
+                let Some(playlist) = playlists
+                    .iter()
+                    .find(|p| p.name == source.playlist || p.id == source.playlist)
+                else {
+                    warn!(
+                        "source playlist \"{}\" of merged playlist \"{}\" not found, skipping it",
+                        source.playlist, merged.name
+                    );
+                    continue;
+                };
+                merge_songs(&mut songs, &playlist.songs, matcher);
+            }
+            info!(
+                "merged {} songs into playlist \"{}\"",
+                songs.len(),
+                merged.name
+            );
+            merged_playlists.push(Playlist {
+                id: format!("{}{}", Self::MERGE_ID_PREFIX, merged.name),
+                name: merged.name.clone(),
+                description: None,
+                public: None,
+                cover_url: None,
+                songs,
+            });
+        }
+        src_playlists.extend(merged_playlists);
+        Ok(())
+    }
+
+    /// Append a playlist made of the source likes, if a likes playlist is mapped
+    pub fn apply_likes_playlist(&self, src_likes: &[Song], src_playlists: &mut Vec<Playlist>) {
+        let Some(name) = &self.likes_playlist else {
+            return;
+        };
+        info!(
+            "synchronizing {} likes into playlist \"{}\"",
+            src_likes.len(),
+            name
+        );
+        src_playlists.push(Playlist {
+            id: Self::LIKES_ID.to_string(),
+            name: name.clone(),
+            description: None,
+            public: None,
+            cover_url: None,
+            songs: src_likes.to_vec(),
+        });
+    }
+
+    /// Remove the source playlists mapped to the destination likes, and return their songs
+    pub fn take_likes_playlists(
+        &self,
+        src_playlists: &mut Vec<Playlist>,
+        matcher: &dyn Matcher,
+    ) -> Vec<Song> {
+        let mut songs = vec![];
+        for name in &self.to_likes {
+            let Some(pos) = src_playlists
+                .iter()
+                .position(|p| p.name == *name || p.id == *name)
+            else {
+                warn!(
+                    "source playlist \"{}\" mapped to likes not found, skipping it",
+                    name
+                );
+                continue;
+            };
+            let playlist = src_playlists.remove(pos);
+            info!("synchronizing playlist \"{}\" into likes", playlist.name);
+            merge_songs(&mut songs, &playlist.songs, matcher);
+        }
+        songs
+    }
+
+    /// Name of the destination playlist matching the source playlist
+    pub fn dst_name(&self, src_playlist: &Playlist) -> Result<String> {
+        if let Some(name) = self
+            .rename
+            .get(&src_playlist.id)
+            .or_else(|| self.rename.get(&src_playlist.name))
+        {
+            return Ok(name.clone());
+        }
+        // merged and likes playlists names are already destination names
+        if src_playlist.id.starts_with(Self::MERGE_ID_PREFIX) || src_playlist.id == Self::LIKES_ID {
+            return Ok(src_playlist.name.clone());
+        }
+        let mut name = src_playlist.name.clone();
+        for rule in &self.rules {
+            name = rule.apply(&name)?;
+        }
+        Ok(name)
+    }
+}
+
+/// Union of two song lists, songs matching an already present song are skipped
+pub fn merge_songs(songs: &mut Vec<Song>, other: &[Song], matcher: &dyn Matcher) {
+    for song in other {
+        if !songs.iter().any(|s| s.compare(song, matcher)) {
+            songs.push(song.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::DefaultMatcher;
+    use crate::music_api::MusicApiType;
+
+    fn song(source: MusicApiType, id: &str, name: &str) -> Song {
+        Song::test(source, id, name)
+            .with_album("album")
+            .with_duration(200)
+    }
+
+    #[test]
+    fn test_apply_merges() {
+        let mut src_playlists = vec![
+            Playlist {
+                id: "1".to_string(),
+                name: "Chill".to_string(),
+                description: None,
+                public: None,
+                cover_url: None,
+                songs: vec![
+                    song(MusicApiType::Spotify, "a", "Song A").with_isrc("USAAA0000001"),
+                    song(MusicApiType::Spotify, "b", "Song B").with_isrc("USAAA0000002"),
+                ],
+            },
+            Playlist {
+                id: "2".to_string(),
+                name: "Party".to_string(),
+                description: None,
+                public: None,
+                cover_url: None,
+                songs: vec![
+                    song(MusicApiType::Spotify, "b", "Song B").with_isrc("USAAA0000002"),
+                    song(MusicApiType::Spotify, "c", "Song C").with_isrc("USAAA0000003"),
+                ],
+            },
+        ];
+        let mapping = Mapping {
+            merge: vec![MergedPlaylist {
+                name: "Team Mix".to_string(),
+                sources: vec![
+                    MergeSource {
+                        playlist: "Chill".to_string(),
+                        file: None,
+                    },
+                    MergeSource {
+                        playlist: "2".to_string(),
+                        file: None,
+                    },
+                    MergeSource {
+                        playlist: "Unknown".to_string(),
+                        file: None,
+                    },
+                ],
+            }],
+            ..Default::default()
+        };
+        mapping
+            .apply_merges(&mut src_playlists, &DefaultMatcher::default())
+            .unwrap();
+
+        let merged = src_playlists.last().unwrap();
+        assert_eq!(merged.name, "Team Mix");
+        let ids: Vec<&str> = merged.songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_dst_name() {
+        let mapping: Mapping = serde_json::from_str(
+            r#"{
+                "rename": { "37i9dQZF1DX": "Renamed by id", "Old Name": "New Name" },
+                "rules": [
+                    { "replace": { "pattern": "[/:]", "replacement": "-" } },
+                    { "prefix": "[SP] " },
+                    { "suffix": " (sync)" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let playlist = |id: &str, name: &str| Playlist {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: None,
+            public: None,
+            cover_url: None,
+            songs: vec![],
+        };
+
+        let name = mapping.dst_name(&playlist("37i9dQZF1DX", "Some Name"));
+        assert_eq!(name.unwrap(), "Renamed by id");
+        let name = mapping.dst_name(&playlist("1", "Old Name"));
+        assert_eq!(name.unwrap(), "New Name");
+        let name = mapping.dst_name(&playlist("1", "Rock/Metal: 2024"));
+        assert_eq!(name.unwrap(), "[SP] Rock-Metal- 2024 (sync)");
+        let name = mapping.dst_name(&playlist("merge:Team Mix", "Team Mix"));
+        assert_eq!(name.unwrap(), "Team Mix");
+    }
+
+    #[test]
+    fn test_likes_mapping() {
+        let mapping: Mapping = serde_json::from_str(
+            r#"{
+                "likes_playlist": "Liked Songs",
+                "to_likes": ["Favorites", "3", "Unknown"],
+                "rules": [{ "prefix": "[SP] " }]
+            }"#,
+        )
+        .unwrap();
+        let playlist = |id: &str, name: &str, songs: Vec<Song>| Playlist {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: None,
+            public: None,
+            cover_url: None,
+            songs,
+        };
+        let a = song(MusicApiType::Spotify, "a", "Song A");
+        let b = song(MusicApiType::Spotify, "b", "Song B");
+        let mut src_playlists = vec![
+            playlist("1", "Favorites", vec![a.clone()]),
+            playlist("2", "Rock", vec![]),
+            playlist("3", "Top", vec![a.clone(), b.clone()]),
+        ];
+
+        let songs = mapping.take_likes_playlists(&mut src_playlists, &DefaultMatcher::default());
+        let ids: Vec<&str> = songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(src_playlists.len(), 1);
+
+        mapping.apply_likes_playlist(&[b], &mut src_playlists);
+        let likes = src_playlists.last().unwrap();
+        assert_eq!(likes.songs.len(), 1);
+        assert_eq!(mapping.dst_name(likes).unwrap(), "Liked Songs");
+        let name = mapping.dst_name(&src_playlists[0]);
+        assert_eq!(name.unwrap(), "[SP] Rock");
+    }
+
+    #[test]
+    fn test_merge_songs_cross_platform() {
+        let mut songs = vec![song(MusicApiType::Spotify, "a", "Song A")];
+        let other = [
+            song(MusicApiType::YtMusic, "yt_a", "Song A"),
+            song(MusicApiType::YtMusic, "yt_b", "Another One"),
+        ];
+        merge_songs(&mut songs, &other, &DefaultMatcher::default());
+        let ids: Vec<&str> = songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["a", "yt_b"]);
+    }
+}
@@ -0,0 +1,563 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strsim::normalized_levenshtein;
+use tracing::debug;
+
+use crate::ConfigArgs;
+use crate::music_api::Song;
+use crate::utils::transliterate;
+
+/// Named presets of matching thresholds
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchProfile {
+    /// Close names and albums, every credited artist must match
+    Strict,
+    #[default]
+    Balanced,
+    /// Tolerates inaccurate durations and album names, e.g. for Youtube Music
+    Lenient,
+}
+
+impl MatchProfile {
+    pub fn thresholds(self) -> MatchThresholds {
+        match self {
+            Self::Strict => MatchThresholds {
+                name: 0.9,
+                album: 0.9,
+                artists: 1.0,
+                duration_tolerance: 1,
+                require_artist: true,
+                minor_versions: true,
+                transliterate: false,
+            },
+            Self::Balanced => MatchThresholds {
+                name: 0.8,
+                album: 0.8,
+                artists: 0.5,
+                duration_tolerance: 1,
+                require_artist: false,
+                minor_versions: false,
+                transliterate: false,
+            },
+            Self::Lenient => MatchThresholds {
+                name: 0.7,
+                album: 0.6,
+                artists: 0.0,
+                duration_tolerance: 5,
+                require_artist: false,
+                minor_versions: false,
+                transliterate: false,
+            },
+        }
+    }
+}
+
+/// Tunables used by the [`DefaultMatcher`]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MatchThresholds {
+    /// Minimum song name resemblance score
+    pub name: f64,
+    /// Minimum album name resemblance score
+    pub album: f64,
+    /// Maximum duration difference in seconds
+    pub duration_tolerance: usize,
+    /// Minimum ratio of common artists, see [`DefaultMatcher::artists_score`]
+    pub artists: f64,
+    /// Reject the songs without artists metadata
+    pub require_artist: bool,
+    /// Also distinguish the minor versions, e.g. remasters and radio edits
+    pub minor_versions: bool,
+    /// Compare the names transliterated to ASCII, e.g. "Кино" and "Kino"
+    #[serde(default)]
+    pub transliterate: bool,
+}
+
+impl Default for MatchThresholds {
+    fn default() -> Self {
+        MatchProfile::default().thresholds()
+    }
+}
+
+impl MatchThresholds {
+    /// Thresholds of the configured profile, overridden by the individual options
+    pub fn from_config(config: &ConfigArgs) -> Self {
+        let mut thresholds = config.match_profile.thresholds();
+        if let Some(name) = config.name_threshold {
+            thresholds.name = name;
+        }
+        if let Some(album) = config.album_threshold {
+            thresholds.album = album;
+        }
+        if let Some(artists) = config.artist_threshold {
+            thresholds.artists = artists;
+        }
+        if let Some(duration_tolerance) = config.duration_tolerance {
+            thresholds.duration_tolerance = duration_tolerance;
+        }
+        thresholds.require_artist |= config.require_artist;
+        thresholds.transliterate |= config.transliterate;
+        thresholds
+    }
+}
+
+/// How the destination song of a source song was found
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchPath {
+    /// ISRC lookup
+    Isrc,
+    /// Name, artists and album queries
+    Fuzzy,
+    /// Manual match from the overrides file
+    Override,
+    /// Manual match picked during the interactive review
+    Review,
+}
+
+/// Detailed result of the comparison between a source song and a candidate song
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct MatchScore {
+    /// ISRC equality, None if one of the songs has no ISRC
+    pub isrc: Option<bool>,
+    /// Song name resemblance score
+    pub title: f64,
+    /// Album name resemblance score, None if the albums were not compared
+    pub album: Option<f64>,
+    /// Ratio of the song artists found in the candidate artists, None if the artists
+    /// were not compared
+    pub artists: Option<f64>,
+    /// Whether the versions and explicit flags are compatible, None if they were not
+    /// compared
+    pub version: Option<bool>,
+    /// Duration difference in seconds
+    pub duration_diff: usize,
+    /// Whether the candidate is considered to be the same song
+    pub is_match: bool,
+    /// Overall confidence, between 0 and 1
+    pub confidence: f64,
+}
+
+impl MatchScore {
+    fn exact(is_match: bool) -> Self {
+        Self {
+            is_match,
+            confidence: if is_match { 1.0 } else { 0.0 },
+            ..Default::default()
+        }
+    }
+
+    /// Whether no other candidate can do better: same ISRC, or same title, album and
+    /// duration
+    pub fn is_certain(&self) -> bool {
+        self.is_match
+            && (self.isrc == Some(true)
+                || (self.title >= 1.0 && self.album == Some(1.0) && self.duration_diff == 0))
+    }
+}
+
+pub trait Matcher: Send + Sync {
+    fn score(&self, song: &Song, candidate: &Song) -> MatchScore;
+
+    fn is_match(&self, song: &Song, candidate: &Song) -> bool {
+        self.score(song, candidate).is_match
+    }
+
+    /// Highest scoring matching candidate, the first one wins in case of a tie
+    fn best_match(&self, song: &Song, candidates: Vec<Song>) -> Option<(Song, MatchScore)> {
+        let mut best: Option<(Song, MatchScore)> = None;
+        for candidate in candidates {
+            let score = self.score(song, &candidate);
+            if !score.is_match {
+                continue;
+            }
+            if best
+                .as_ref()
+                .is_none_or(|(_, b)| score.confidence > b.confidence)
+            {
+                best = Some((candidate, score));
+            }
+        }
+        best
+    }
+}
+
+/// ISRC when available, otherwise name, album and duration resemblance
+#[derive(Debug, Clone, Default)]
+pub struct DefaultMatcher {
+    thresholds: MatchThresholds,
+}
+
+impl DefaultMatcher {
+    pub fn new(thresholds: MatchThresholds) -> Self {
+        Self { thresholds }
+    }
+
+    pub fn from_config(config: &ConfigArgs) -> Self {
+        Self::new(MatchThresholds::from_config(config))
+    }
+
+    const TITLE_WEIGHT: f64 = 0.4;
+    const ALBUM_WEIGHT: f64 = 0.2;
+    const ARTISTS_WEIGHT: f64 = 0.2;
+    const DURATION_WEIGHT: f64 = 0.2;
+
+    /// Cleaned name, transliterated if enabled, used for the comparisons only as the
+    /// search queries keep the original script
+    fn compared_name(&self, name: String) -> String {
+        if self.thresholds.transliterate {
+            transliterate(&name)
+        } else {
+            name
+        }
+    }
+
+    /// Ratio of the smallest artist set found in the other one, the credits order and
+    /// the way they are split across the metadata and the song name don't matter
+    fn artists_score(&self, song: &Song, candidate: &Song) -> Option<f64> {
+        let credits = |s: &Song| -> Vec<String> {
+            s.artist_credits()
+                .into_iter()
+                .map(|a| self.compared_name(a))
+                .collect()
+        };
+        let credits1 = credits(song);
+        let credits2 = credits(candidate);
+        if credits1.is_empty() || credits2.is_empty() {
+            return None;
+        }
+        let (small, large) = if credits1.len() <= credits2.len() {
+            (&credits1, &credits2)
+        } else {
+            (&credits2, &credits1)
+        };
+        let found = small
+            .iter()
+            .filter(|a1| large.iter().any(|a2| normalized_levenshtein(a1, a2) >= 0.8))
+            .count();
+        Some(found as f64 / small.len() as f64)
+    }
+
+    fn versions_match(song: &Song, candidate: &Song, minor_versions: bool) -> bool {
+        if let (Some(explicit1), Some(explicit2)) = (song.explicit, candidate.explicit)
+            && explicit1 != explicit2
+        {
+            return false;
+        }
+        let versions = |s: &Song| {
+            s.versions
+                .iter()
+                .copied()
+                .filter(|v| minor_versions || !v.is_minor())
+                .collect::<Vec<_>>()
+        };
+        versions(song) == versions(candidate)
+    }
+}
+
+impl Matcher for DefaultMatcher {
+    fn score(&self, song: &Song, candidate: &Song) -> MatchScore {
+        if song.source == candidate.source {
+            return MatchScore::exact(song.id == candidate.id);
+        }
+        if let (Some(isrc1), Some(isrc2)) = (&song.isrc, &candidate.isrc) {
+            let mut score = MatchScore::exact(isrc1 == isrc2);
+            score.isrc = Some(score.is_match);
+            return score;
+        }
+
+        // Check song name resemblance
+        let title = normalized_levenshtein(
+            &self.compared_name(song.clean_name()),
+            &self.compared_name(candidate.clean_name()),
+        )
+        .abs();
+
+        // INFO: Artists are not always in the same order, and for certain platforms the
+        // featured artists are included in the song name but not in the metadata
+        let artists = self.artists_score(song, candidate);
+
+        // Check song duration resemblance
+        // NOTE: YtMusic duration is sometimes garbage, it's incorrect on certain songs
+        // it's still better to use it for accuracy
+        let duration_diff = (song.duration_ms / 1000).abs_diff(candidate.duration_ms / 1000);
+
+        let mut album = None;
+        if let (Some(album1), Some(album2)) = (&song.album, &candidate.album) {
+            // INFO: Sometimes Youtube Music maps the album song to the Youtube Video
+            // Sometimes, the album song is just suppressed from the 'Songs' filter
+            // In these cases, we can get the single instead so we shouldn't compare album
+            // names
+            if !song.is_single() && !candidate.is_single() {
+                // Check album name resemblance
+                let score = normalized_levenshtein(
+                    &self.compared_name(album1.clean_name()),
+                    &self.compared_name(album2.clean_name()),
+                );
+                album = Some(score.abs());
+            }
+        }
+
+        let thresholds = &self.thresholds;
+        let version = Self::versions_match(song, candidate, thresholds.minor_versions);
+        let is_match = if title < thresholds.name {
+            false
+        } else if duration_diff > thresholds.duration_tolerance {
+            debug!(
+                "Duration: {} vs {} --> {} VS {}",
+                song.duration_ms / 1000,
+                candidate.duration_ms / 1000,
+                song,
+                candidate
+            );
+            false
+        } else if !version {
+            debug!(
+                "Version: {:?} vs {:?} --> {} VS {}",
+                song.versions, candidate.versions, song, candidate
+            );
+            false
+        } else {
+            album.is_none_or(|score| score >= thresholds.album)
+                && artists.map_or(!thresholds.require_artist, |score| {
+                    score >= thresholds.artists
+                })
+        };
+
+        let duration = (1.0 - duration_diff as f64 / 10.0).max(0.0);
+        let mut total = title * Self::TITLE_WEIGHT + duration * Self::DURATION_WEIGHT;
+        let mut weights = Self::TITLE_WEIGHT + Self::DURATION_WEIGHT;
+        if let Some(album) = album {
+            total += album * Self::ALBUM_WEIGHT;
+            weights += Self::ALBUM_WEIGHT;
+        }
+        if let Some(artists) = artists {
+            total += artists * Self::ARTISTS_WEIGHT;
+            weights += Self::ARTISTS_WEIGHT;
+        }
+
+        MatchScore {
+            isrc: None,
+            title,
+            album,
+            artists,
+            version: Some(version),
+            duration_diff,
+            is_match,
+            confidence: total / weights,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music_api::MusicApiType;
+
+    fn song(source: MusicApiType, name: &str, album: &str, artist: &str, secs: usize) -> Song {
+        Song::test(source, name, name)
+            .with_album(album)
+            .with_artist(artist)
+            .with_duration(secs)
+    }
+
+    #[test]
+    fn test_default_matcher_score() {
+        let src = song(MusicApiType::Spotify, "Get Lucky", "RAM", "Daft Punk", 369);
+
+        let cand = song(MusicApiType::Tidal, "Get Lucky", "RAM", "Daft Punk", 370);
+        let score = DefaultMatcher::default().score(&src, &cand);
+        assert!(score.is_match);
+        assert_eq!(score.duration_diff, 1);
+        assert_eq!(score.artists, Some(1.0));
+
+        let cand = song(MusicApiType::Tidal, "Get Lucky", "RAM", "Daft Punk", 380);
+        assert!(!DefaultMatcher::default().is_match(&src, &cand));
+
+        let cand = song(
+            MusicApiType::Tidal,
+            "Get Lucky",
+            "Other Album",
+            "Daft Punk",
+            369,
+        );
+        assert!(!DefaultMatcher::default().is_match(&src, &cand));
+
+        // durations of 0 seconds used to underflow
+        let cand = song(MusicApiType::Tidal, "Get Lucky", "RAM", "Daft Punk", 0);
+        assert!(!DefaultMatcher::default().is_match(&src, &cand));
+    }
+
+    #[test]
+    fn test_match_profiles() {
+        let src = song(MusicApiType::Spotify, "Get Lucky", "RAM", "Daft Punk", 369);
+        let strict = DefaultMatcher::new(MatchProfile::Strict.thresholds());
+        let lenient = DefaultMatcher::new(MatchProfile::Lenient.thresholds());
+
+        let cand = song(MusicApiType::YtMusic, "Get Lucky", "RAM", "Daft Punk", 373);
+        assert!(!DefaultMatcher::default().is_match(&src, &cand));
+        assert!(lenient.is_match(&src, &cand));
+
+        let cand = song(
+            MusicApiType::YtMusic,
+            "Get Lucky",
+            "RAM",
+            "Karaoke Band",
+            369,
+        );
+        assert!(!DefaultMatcher::default().is_match(&src, &cand));
+        assert!(lenient.is_match(&src, &cand));
+
+        let mut cand = song(MusicApiType::YtMusic, "Get Lucky", "RAM", "Daft Punk", 369);
+        cand.artists.clear();
+        assert!(DefaultMatcher::default().is_match(&src, &cand));
+        assert!(!strict.is_match(&src, &cand));
+    }
+
+    #[test]
+    fn test_versions_match() {
+        let src = song(MusicApiType::Spotify, "Get Lucky", "RAM", "Daft Punk", 369);
+        let matcher = DefaultMatcher::default();
+
+        let cand = song(
+            MusicApiType::Tidal,
+            "Get Lucky (Live)",
+            "RAM",
+            "Daft Punk",
+            369,
+        );
+        let score = matcher.score(&src, &cand);
+        assert_eq!(score.version, Some(false));
+        assert!(!score.is_match);
+        let cand = song(
+            MusicApiType::YtMusic,
+            "Get Lucky - Remix",
+            "RAM",
+            "Daft Punk",
+            369,
+        );
+        assert!(!matcher.is_match(&src, &cand));
+
+        // remasters are only distinguished by the strict profile
+        let cand = song(
+            MusicApiType::Tidal,
+            "Get Lucky (2023 Remaster)",
+            "RAM",
+            "Daft Punk",
+            369,
+        );
+        assert!(matcher.is_match(&src, &cand));
+        let strict = DefaultMatcher::new(MatchProfile::Strict.thresholds());
+        assert!(!strict.is_match(&src, &cand));
+
+        let mut src = src;
+        src.explicit = Some(true);
+        let mut cand = song(MusicApiType::Tidal, "Get Lucky", "RAM", "Daft Punk", 369);
+        cand.explicit = Some(false);
+        assert!(!matcher.is_match(&src, &cand));
+        cand.explicit = None;
+        assert!(matcher.is_match(&src, &cand));
+    }
+
+    #[test]
+    fn test_artists_score() {
+        let src = song(
+            MusicApiType::Spotify,
+            "Get Lucky",
+            "RAM",
+            "Daft Punk, Pharrell Williams",
+            369,
+        );
+        // credits in a different order, partly in the song name
+        let cand = song(
+            MusicApiType::YtMusic,
+            "Get Lucky (feat. Pharrell Williams)",
+            "RAM",
+            "Daft Punk",
+            369,
+        );
+        assert_eq!(
+            DefaultMatcher::default().artists_score(&src, &cand),
+            Some(1.0)
+        );
+        let cand = song(
+            MusicApiType::Tidal,
+            "Get Lucky",
+            "RAM",
+            "Pharrell Williams & Daft Punk",
+            369,
+        );
+        assert_eq!(
+            DefaultMatcher::default().artists_score(&src, &cand),
+            Some(1.0)
+        );
+        let strict = DefaultMatcher::new(MatchProfile::Strict.thresholds());
+        assert!(strict.is_match(&src, &cand));
+
+        // only the primary artist is credited
+        let cand = song(MusicApiType::Tidal, "Get Lucky", "RAM", "Daft Punk", 369);
+        assert_eq!(
+            DefaultMatcher::default().artists_score(&src, &cand),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn test_best_match() {
+        let src = song(MusicApiType::Spotify, "Get Lucky", "RAM", "Daft Punk", 369);
+        let candidates = vec![
+            song(
+                MusicApiType::YtMusic,
+                "Get Lucky",
+                "RAM",
+                "Karaoke Band",
+                370,
+            ),
+            song(MusicApiType::YtMusic, "Get Lucky", "RAM", "Daft Punk", 369),
+            song(
+                MusicApiType::YtMusic,
+                "Get Lucky",
+                "Other",
+                "Daft Punk",
+                369,
+            ),
+        ];
+        let (best, score) = DefaultMatcher::default()
+            .best_match(&src, candidates)
+            .unwrap();
+        assert_eq!(best.artists[0].name, "Daft Punk");
+        assert!((score.confidence - 1.0).abs() < f64::EPSILON);
+        assert!(score.is_certain());
+
+        let other = song(MusicApiType::YtMusic, "Get Lucky", "RAM", "Daft Punk", 370);
+        let score = DefaultMatcher::default().score(&src, &other);
+        assert!(score.is_match && !score.is_certain());
+    }
+
+    #[test]
+    fn test_transliterate() {
+        let src = song(
+            MusicApiType::Spotify,
+            "Группа крови",
+            "Группа крови",
+            "Кино",
+            285,
+        );
+        let cand = song(
+            MusicApiType::YtMusic,
+            "Gruppa Krovi",
+            "Gruppa Krovi",
+            "Kino",
+            285,
+        );
+        assert!(!DefaultMatcher::default().is_match(&src, &cand));
+
+        let thresholds = MatchThresholds {
+            transliterate: true,
+            ..Default::default()
+        };
+        let score = DefaultMatcher::new(thresholds).score(&src, &cand);
+        assert!(score.is_match);
+        assert_eq!(score.artists, Some(1.0));
+    }
+}
@@ -1,22 +1,80 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
 use async_trait::async_trait;
 use color_eyre::eyre::Result;
 use futures::future::try_join_all;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use strsim::normalized_levenshtein;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use crate::utils::generic_name_clean;
+use crate::ConfigArgs;
+use crate::matcher::{DefaultMatcher, MatchPath, MatchScore, Matcher};
+use crate::utils::{generic_name_clean, normalize_unicode, split_artist_credits};
 
 pub const PLAYLIST_DESC: &str = "Playlist created by SyncDisBoi";
 
+static NAME_FEAT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(?:feat\.?|ft\.|featuring)\s+([^)\]]+)").unwrap());
+
+static ENCLOSURE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[(\[]([^()\[\]]*)[)\]]").unwrap());
+static VERSION_RES: LazyLock<Vec<(Regex, SongVersion)>> = LazyLock::new(|| {
+    [
+        (
+            r"\b(?:re-?mix(?:ed)?|rmx|rework|bootleg|\w+ mix)\b",
+            SongVersion::Remix,
+        ),
+        (
+            r"\b(?:live|en vivo|ao vivo|en directo)\b",
+            SongVersion::Live,
+        ),
+        (
+            r"\b(?:acoustic|acustico|unplugged|stripped)\b",
+            SongVersion::Acoustic,
+        ),
+        (r"\bremaster(?:ed)?\b", SongVersion::Remaster),
+        (r"\binstrumental\b", SongVersion::Instrumental),
+        (r"\bkaraoke\b", SongVersion::Karaoke),
+        (r"\bdemo\b", SongVersion::Demo),
+        (
+            r"\b(?:edit|single version|radio version)\b",
+            SongVersion::Edit,
+        ),
+    ]
+    .into_iter()
+    .map(|(re, version)| (Regex::new(re).unwrap(), version))
+    .collect()
+});
+
+static SPOTIFY_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:open\.spotify\.com/(?:[\w-]+/)?track/|spotify:track:)(\w+)").unwrap()
+});
+static YTMUSIC_URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:youtube\.com/watch\?(?:.*&)?v=|youtu\.be/)([\w-]+)").unwrap());
+static TIDAL_URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"tidal\.com/(?:browse/)?track/(\d+)").unwrap());
+
 pub type DynMusicApi = Box<dyn MusicApi + Sync>;
 
 #[async_trait]
 pub trait MusicApi {
     fn api_type(&self) -> MusicApiType;
     fn country_code(&self) -> &str;
+    fn config(&self) -> &ConfigArgs;
 
-    async fn create_playlist(&self, name: &str, public: bool) -> Result<Playlist>;
+    /// Matcher with the profile and thresholds of the configuration
+    fn matcher(&self) -> DefaultMatcher {
+        DefaultMatcher::from_config(self.config())
+    }
+
+    async fn create_playlist(
+        &self,
+        name: &str,
+        description: &str,
+        public: bool,
+    ) -> Result<Playlist>;
     async fn get_playlists_info(&self) -> Result<Vec<Playlist>>;
     async fn get_playlist_songs(&self, id: &str) -> Result<Vec<Song>>;
 
@@ -42,8 +100,70 @@ pub trait MusicApi {
         songs_ids: &[Song],
     ) -> Result<()>;
     async fn delete_playlist(&self, playlist: Playlist) -> Result<()>;
+    async fn update_playlist_details(
+        &self,
+        playlist: &mut Playlist,
+        description: &str,
+        public: bool,
+    ) -> Result<()>;
+    async fn upload_playlist_cover(&self, playlist: &mut Playlist, jpeg: &[u8]) -> Result<()>;
 
-    async fn search_song(&self, song: &Song) -> Result<Option<Song>>;
+    async fn search_song(&self, song: &Song) -> Result<Option<Song>> {
+        Ok(self.search_song_match(song).await?.map(|(song, _)| song))
+    }
+    /// Destination song matching the source song, along with the search path that found it
+    async fn search_song_match(&self, song: &Song) -> Result<Option<(Song, MatchPath)>>;
+    /// Destination song with the given ISRC, None if the platform has none
+    async fn search_isrc(&self, song: &Song, isrc: &str) -> Result<Option<Song>>;
+    /// Free-text song search, the results are in the platform order
+    async fn search_query(&self, query: &str, limit: usize) -> Result<Vec<Song>>;
+
+    /// Highest scoring candidate among the top results of all the queries, starting from
+    /// the last one. The search stops early on a certain match
+    async fn search_best_candidate(
+        &self,
+        song: &Song,
+        mut queries: Vec<String>,
+        limit: usize,
+    ) -> Result<Option<(Song, MatchScore)>> {
+        let mut best: Option<(Song, MatchScore)> = None;
+        while let Some(query) = queries.pop() {
+            let candidates = self.search_query(&query, limit).await?;
+            if let Some((res_song, score)) = self.matcher().best_match(song, candidates)
+                && best
+                    .as_ref()
+                    .is_none_or(|(_, b)| score.confidence > b.confidence)
+            {
+                let certain = score.is_certain();
+                best = Some((res_song, score));
+                if certain {
+                    break;
+                }
+            }
+        }
+        Ok(best)
+    }
+
+    /// Fuzzy search fallback, used when the song has no ISRC or the ISRC lookup failed
+    async fn search_fuzzy(
+        &self,
+        song: &Song,
+        queries: Vec<String>,
+    ) -> Result<Option<(Song, MatchPath)>> {
+        // ISRCs can differ across regions, the fuzzy search must not compare them
+        let fuzzy_song = Song {
+            isrc: None,
+            ..song.clone()
+        };
+        let limit = self.config().search_candidates;
+        let res = self
+            .search_best_candidate(&fuzzy_song, queries, limit)
+            .await?;
+        Ok(res.map(|(res_song, score)| {
+            debug!("match confidence {:.2} --> {}", score.confidence, res_song);
+            (res_song, MatchPath::Fuzzy)
+        }))
+    }
 
     async fn search_songs(&self, songs: &[Song]) -> Result<Vec<Option<Song>>> {
         let mut requests = vec![];
@@ -55,7 +175,73 @@ pub trait MusicApi {
     }
 
     async fn add_likes(&self, songs: &[Song]) -> Result<()>;
+    async fn remove_likes(&self, songs: &[Song]) -> Result<()>;
     async fn get_likes(&self) -> Result<Vec<Song>>;
+
+    async fn get_saved_albums(&self) -> Result<Vec<Album>>;
+    async fn search_album(&self, album: &Album) -> Result<Option<Album>>;
+    async fn add_saved_albums(&self, albums: &[Album]) -> Result<()>;
+
+    async fn get_followed_artists(&self) -> Result<Vec<Artist>>;
+    /// Free-text artist search, the results are in the platform order
+    async fn search_artist_query(&self, query: &str, limit: usize) -> Result<Vec<Artist>>;
+    /// Most popular songs of the artist, empty if the platform doesn't expose their ISRC
+    async fn get_artist_top_songs(&self, artist: &Artist) -> Result<Vec<Song>>;
+    async fn follow_artists(&self, artists: &[Artist]) -> Result<()>;
+
+    /// Artist with the same normalized name. Homonyms are told apart by the overlap of
+    /// their top songs ISRCs with the ones of the source artist, only retrieved from the
+    /// source platform when several artists share the name
+    async fn search_artist(
+        &self,
+        artist: &Artist,
+        src_api: &DynMusicApi,
+    ) -> Result<Option<Artist>> {
+        let mut candidates: Vec<Artist> = self
+            .search_artist_query(&artist.clean_name(), 5)
+            .await?
+            .into_iter()
+            .filter(|a| a.compare(artist))
+            .collect();
+        if candidates.len() <= 1 {
+            return Ok(candidates.pop());
+        }
+        let top_songs = match src_api.get_artist_top_songs(artist).await {
+            Ok(songs) => songs,
+            Err(e) => {
+                warn!(
+                    "failed to retrieve top songs of artist {}: {}",
+                    artist.name, e
+                );
+                vec![]
+            }
+        };
+        let isrcs: HashSet<&str> = top_songs.iter().filter_map(|s| s.isrc.as_deref()).collect();
+        // candidate whose top songs ISRCs are unknown
+        let mut fallback = None;
+        for candidate in candidates {
+            if isrcs.is_empty() {
+                return Ok(Some(candidate));
+            }
+            let candidate_songs = self.get_artist_top_songs(&candidate).await?;
+            let mut candidate_isrcs = candidate_songs
+                .iter()
+                .filter_map(|s| s.isrc.as_deref())
+                .peekable();
+            if candidate_isrcs.peek().is_none() {
+                fallback.get_or_insert(candidate);
+                continue;
+            }
+            if candidate_isrcs.any(|isrc| isrcs.contains(isrc)) {
+                return Ok(Some(candidate));
+            }
+            debug!(
+                "no common top song, skipping homonym artist: {}",
+                candidate.name
+            );
+        }
+        Ok(fallback)
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
@@ -73,8 +259,37 @@ impl MusicApiType {
             MusicApiType::Tidal => "tidal",
         }
     }
+
+    /// ID of the song shared with a URL, e.g. `https://open.spotify.com/track/<id>`
+    pub fn parse_song_url(&self, url: &str) -> Option<String> {
+        let re = match self {
+            MusicApiType::Spotify => &*SPOTIFY_URL_RE,
+            MusicApiType::YtMusic => &*YTMUSIC_URL_RE,
+            MusicApiType::Tidal => &*TIDAL_URL_RE,
+        };
+        re.captures(url).map(|c| c[1].to_string())
+    }
 }
 
+/// Some songs were rejected by the platform while the others were added to the playlist
+#[derive(Debug)]
+pub struct PartialAddError {
+    /// Rejected songs, along with the error of the platform
+    pub failed: Vec<(Song, String)>,
+}
+
+impl std::fmt::Display for PartialAddError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} songs could not be added to the playlist",
+            self.failed.len()
+        )
+    }
+}
+
+impl std::error::Error for PartialAddError {}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Playlists(pub Vec<Playlist>);
 
@@ -82,13 +297,24 @@ pub struct Playlists(pub Vec<Playlist>);
 pub struct Songs(pub Vec<Song>);
 
 #[derive(Deserialize, Serialize, Debug)]
+pub struct Albums(pub Vec<Album>);
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Artists(pub Vec<Artist>);
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Playlist {
     pub id: String,
     pub name: String,
+    pub description: Option<String>,
+    // None if the platform doesn't expose the playlist visibility
+    pub public: Option<bool>,
+    pub cover_url: Option<String>,
     pub songs: Vec<Song>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(from = "SerializedSong")]
 pub struct Song {
     pub source: MusicApiType,
     pub id: String,
@@ -98,6 +324,99 @@ pub struct Song {
     pub album: Option<Album>,
     pub artists: Vec<Artist>,
     pub duration_ms: usize,
+    /// Version descriptors parsed from the song name, empty for the original version
+    pub versions: Vec<SongVersion>,
+    // None if the platform doesn't expose the explicit flag
+    pub explicit: Option<bool>,
+    /// UNIX timestamp at which the song was liked or added to its playlist, None if the
+    /// platform doesn't expose it
+    pub added_at: Option<i64>,
+}
+
+/// Deserialized song, the versions are missing from the exports of older releases
+#[derive(Deserialize)]
+struct SerializedSong {
+    source: MusicApiType,
+    id: String,
+    sid: Option<String>,
+    isrc: Option<String>,
+    name: String,
+    album: Option<Album>,
+    artists: Vec<Artist>,
+    duration_ms: usize,
+    versions: Option<Vec<SongVersion>>,
+    explicit: Option<bool>,
+    #[serde(default)]
+    added_at: Option<i64>,
+}
+
+impl From<SerializedSong> for Song {
+    fn from(song: SerializedSong) -> Self {
+        Self {
+            versions: song
+                .versions
+                .unwrap_or_else(|| SongVersion::parse(&song.name)),
+            source: song.source,
+            id: song.id,
+            sid: song.sid,
+            isrc: song.isrc,
+            name: song.name,
+            album: song.album,
+            artists: song.artists,
+            duration_ms: song.duration_ms,
+            explicit: song.explicit,
+            added_at: song.added_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SongVersion {
+    Remix,
+    Live,
+    Acoustic,
+    Remaster,
+    Instrumental,
+    Karaoke,
+    Demo,
+    Edit,
+}
+
+impl SongVersion {
+    /// Minor versions are usually the same recording, labeled differently across
+    /// platforms
+    pub fn is_minor(self) -> bool {
+        matches!(self, Self::Remaster | Self::Edit)
+    }
+
+    /// Version descriptors found in the enclosed or dash-separated parts of a song name,
+    /// the song title itself is ignored
+    pub fn parse(name: &str) -> Vec<Self> {
+        let name = normalize_unicode(name);
+        let mut descriptors: Vec<&str> = ENCLOSURE_RE
+            .captures_iter(&name)
+            .filter_map(|c| c.get(1))
+            .map(|m| m.as_str())
+            .collect();
+        if let Some((_, rest)) = name.split_once(" - ") {
+            descriptors.push(rest);
+        }
+
+        let mut versions = vec![];
+        for descriptor in descriptors {
+            // e.g. "Original Mix" is the usual name of the original version on Beatport
+            let descriptor = descriptor.replace("original mix", "");
+            for (re, version) in VERSION_RES.iter() {
+                if re.is_match(&descriptor) {
+                    versions.push(*version);
+                }
+            }
+        }
+        versions.sort();
+        versions.dedup();
+        versions
+    }
 }
 
 impl Song {
@@ -113,6 +432,22 @@ impl Song {
         }
     }
 
+    /// Individual artists credited on the song, including the featured artists only
+    /// mentioned in the song name
+    pub fn artist_credits(&self) -> Vec<String> {
+        let mut credits: Vec<String> = self
+            .artists
+            .iter()
+            .flat_map(|a| split_artist_credits(&a.name))
+            .collect();
+        if let Some(caps) = NAME_FEAT_RE.captures(&self.name) {
+            credits.extend(split_artist_credits(&caps[1]));
+        }
+        credits.sort();
+        credits.dedup();
+        credits
+    }
+
     pub fn is_single(&self) -> bool {
         // TODO: improve this, leverage metadata from APIs when it exists
         if let Some(album) = &self.album {
@@ -122,56 +457,8 @@ impl Song {
         }
     }
 
-    pub fn compare(&self, other: &Self) -> bool {
-        if self.source == other.source {
-            return self.id == other.id;
-        }
-        if self.isrc.is_some() && other.isrc.is_some() {
-            return self.isrc == other.isrc;
-        }
-
-        // Check song name resemblance
-        let name1 = self.clean_name();
-        let name2 = other.clean_name();
-        let score = normalized_levenshtein(&name1, &name2).abs();
-        if score < 0.8 {
-            return false;
-        }
-
-        // INFO: We can't really compare artists names since they are not always the
-        // same order.
-        // For certain platforms they are included in the song name but not in the
-        // metadata
-
-        // Check song duration resemblance
-        // NOTE: YtMusic duration is sometimes garbage, it's incorrect on certain songs
-        // it's still better to use it for accuracy
-        let dur1 = self.duration_ms / 1000;
-        let dur2 = other.duration_ms / 1000;
-
-        // we allow a 1 second difference
-        if !(dur1 - 1..=dur1 + 1).contains(&dur2) {
-            debug!("Duration: {} vs {} --> {} VS {}", dur1, dur2, self, other);
-            return false;
-        }
-
-        if let (Some(album1), Some(album2)) = (&self.album, &other.album) {
-            // INFO: Sometimes Youtube Music maps the album song to the Youtube Video
-            // Sometimes, the album song is just suppressed from the 'Songs' filter
-            // In these cases, we can get the single instead so we shouldn't compare album
-            // names
-            if !self.is_single() && !other.is_single() {
-                // Check album name resemblance
-                let name1 = album1.clean_name();
-                let name2 = album2.clean_name();
-                let score = normalized_levenshtein(&name1, &name2).abs();
-                if score < 0.8 {
-                    return false;
-                }
-            }
-        }
-
-        true
+    pub fn compare(&self, other: &Self, matcher: &dyn Matcher) -> bool {
+        matcher.is_match(self, other)
     }
 
     pub fn build_queries(&self) -> Vec<String> {
@@ -203,9 +490,10 @@ impl Song {
     }
 }
 
+/// Equality with the default thresholds, prefer `compare` with the configured matcher
 impl PartialEq for Song {
     fn eq(&self, other: &Self) -> bool {
-        self.compare(other)
+        self.compare(other, &DefaultMatcher::default())
     }
 }
 
@@ -233,12 +521,72 @@ impl std::fmt::Display for Song {
 pub struct Album {
     pub id: Option<String>,
     pub name: String,
+    // NOTE: only filled for saved albums, song albums rely on the song artists
+    #[serde(default)]
+    pub artists: Vec<Artist>,
+    pub upc: Option<String>,
 }
 
 impl Album {
     pub fn clean_name(&self) -> String {
         generic_name_clean(&self.name)
     }
+
+    pub fn compare(&self, other: &Self) -> bool {
+        if let (Some(upc1), Some(upc2)) = (&self.upc, &other.upc) {
+            // UPC (12 digits) and EAN (13 digits) barcodes only differ by leading zeros
+            return upc1.trim_start_matches('0') == upc2.trim_start_matches('0');
+        }
+
+        // Check album name resemblance
+        let name1 = self.clean_name();
+        let name2 = other.clean_name();
+        let score = normalized_levenshtein(&name1, &name2).abs();
+        if score < 0.8 {
+            return false;
+        }
+
+        // Check that at least one artist is shared, the artists order is not consistent
+        // across platforms
+        if self.artists.is_empty() || other.artists.is_empty() {
+            return true;
+        }
+        self.artists.iter().any(|a1| {
+            let name1 = a1.clean_name();
+            other
+                .artists
+                .iter()
+                .any(|a2| normalized_levenshtein(&name1, &a2.clean_name()) >= 0.8)
+        })
+    }
+
+    pub fn build_queries(&self) -> Vec<String> {
+        let album_name = self.clean_name();
+        // Query: Album
+        let mut queries = vec![album_name.clone()];
+        // Query: Album + Artist
+        for artist in self.artists.iter().rev() {
+            let artist_name = artist.clean_name();
+            queries.push(format!("{} {}", album_name, artist_name));
+        }
+        queries
+    }
+}
+
+impl std::fmt::Display for Album {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let artists = self
+            .artists
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if artists.is_empty() {
+            f.write_str(&self.name)
+        } else {
+            f.write_fmt(format_args!("{} - {}", self.name, artists))
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -252,6 +600,10 @@ impl Artist {
         // TODO: Add ' - ' parsing?
         generic_name_clean(&self.name)
     }
+
+    pub fn compare(&self, other: &Self) -> bool {
+        self.clean_name() == other.clean_name()
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -278,3 +630,173 @@ pub struct OAuthRefreshToken {
     pub scope: String,
     pub token_type: String,
 }
+
+/// Song builder shared by the tests
+#[cfg(test)]
+impl Song {
+    pub(crate) fn test(source: MusicApiType, id: &str, name: &str) -> Self {
+        Self {
+            source,
+            id: id.to_string(),
+            sid: None,
+            isrc: None,
+            name: name.to_string(),
+            album: None,
+            artists: vec![],
+            duration_ms: 0,
+            versions: SongVersion::parse(name),
+            explicit: None,
+            added_at: None,
+        }
+    }
+
+    pub(crate) fn with_isrc(mut self, isrc: &str) -> Self {
+        self.isrc = Some(isrc.to_string());
+        self
+    }
+
+    pub(crate) fn with_album(mut self, album: &str) -> Self {
+        self.album = Some(Album {
+            id: None,
+            name: album.to_string(),
+            artists: vec![],
+            upc: None,
+        });
+        self
+    }
+
+    pub(crate) fn with_artist(mut self, artist: &str) -> Self {
+        self.artists.push(Artist {
+            id: None,
+            name: artist.to_string(),
+        });
+        self
+    }
+
+    pub(crate) fn with_duration(mut self, secs: usize) -> Self {
+        self.duration_ms = secs * 1000;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn album(name: &str, artists: &[&str], upc: Option<&str>) -> Album {
+        Album {
+            id: None,
+            name: name.to_string(),
+            artists: artists
+                .iter()
+                .map(|a| Artist {
+                    id: None,
+                    name: (*a).to_string(),
+                })
+                .collect(),
+            upc: upc.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_album_compare() {
+        // UPC and EAN barcodes of the same album
+        let a1 = album(
+            "Random Access Memories",
+            &["Daft Punk"],
+            Some("886443927087"),
+        );
+        let a2 = album("RAM", &[], Some("0886443927087"));
+        assert!(a1.compare(&a2));
+
+        let a2 = album(
+            "Random Access Memories",
+            &["Daft Punk"],
+            Some("886443927088"),
+        );
+        assert!(!a1.compare(&a2));
+
+        let a2 = album(
+            "Random Access Memories",
+            &["Thomas Bangalter", "Daft Punk"],
+            None,
+        );
+        assert!(a1.compare(&a2));
+
+        let a2 = album("Random Access Memories", &["Tribute Band"], None);
+        assert!(!a1.compare(&a2));
+    }
+
+    #[test]
+    fn test_artist_compare() {
+        let artist = |name: &str| Artist {
+            id: None,
+            name: name.to_string(),
+        };
+        assert!(artist("Beyoncé").compare(&artist("BEYONCE")));
+        assert!(artist("Sigur Rós").compare(&artist("Sigur Ros")));
+        assert!(!artist("Justice").compare(&artist("Justin")));
+    }
+
+    #[test]
+    fn test_parse_song_url() {
+        let id = MusicApiType::Spotify
+            .parse_song_url("https://open.spotify.com/intl-fr/track/2Foc5Q5nqNiosCNqttzHof?si=1");
+        assert_eq!(id.as_deref(), Some("2Foc5Q5nqNiosCNqttzHof"));
+        let id = MusicApiType::YtMusic
+            .parse_song_url("https://music.youtube.com/watch?v=5NV6Rdv1a3I&list=RDAMVM");
+        assert_eq!(id.as_deref(), Some("5NV6Rdv1a3I"));
+        let id = MusicApiType::Tidal.parse_song_url("https://tidal.com/browse/track/26764853/u");
+        assert_eq!(id.as_deref(), Some("26764853"));
+        assert_eq!(
+            MusicApiType::Tidal.parse_song_url("https://open.spotify.com/track/2Foc5Q5"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_song_version_parse() {
+        assert!(SongVersion::parse("Get Lucky").is_empty());
+        assert!(SongVersion::parse("Live Forever").is_empty());
+        assert!(SongVersion::parse("Strobe - Original Mix").is_empty());
+        assert_eq!(SongVersion::parse("Get Lucky (Live)"), [SongVersion::Live]);
+        assert_eq!(
+            SongVersion::parse("Get Lucky - Daft Punk Remix"),
+            [SongVersion::Remix]
+        );
+        assert_eq!(
+            SongVersion::parse("Wonderwall [Remastered 2014] - Live at Knebworth"),
+            [SongVersion::Live, SongVersion::Remaster]
+        );
+        assert_eq!(
+            SongVersion::parse("Layla (Acústico)"),
+            [SongVersion::Acoustic]
+        );
+        assert_eq!(
+            SongVersion::parse("Blinding Lights (Radio Edit)"),
+            [SongVersion::Edit]
+        );
+    }
+
+    #[test]
+    fn test_song_versions_deserialize() {
+        let mut song = serde_json::json!({
+            "source": "Spotify",
+            "id": "1",
+            "sid": null,
+            "isrc": null,
+            "name": "Get Lucky (Live)",
+            "album": null,
+            "artists": [],
+            "duration_ms": 369_000,
+            "explicit": null,
+        });
+        // exported by an older release, without the versions
+        let old: Song = serde_json::from_value(song.clone()).unwrap();
+        assert_eq!(old.versions, [SongVersion::Live]);
+
+        song["versions"] = serde_json::json!([]);
+        let new: Song = serde_json::from_value(song).unwrap();
+        assert!(new.versions.is_empty());
+    }
+}
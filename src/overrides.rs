@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use color_eyre::eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::ConfigArgs;
+use crate::music_api::{MusicApiType, Song};
+
+/// User-provided manual matches, loaded from a JSON file
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Overrides {
+    pub overrides: Vec<MatchOverride>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MatchOverride {
+    /// Platform of the source song, required with `id`
+    pub source: Option<MusicApiType>,
+    /// ID of the source song on its platform
+    pub id: Option<String>,
+    /// ISRC of the source song, used when no platform and ID are given
+    pub isrc: Option<String>,
+    pub destination: MusicApiType,
+    pub target: OverrideTarget,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverrideTarget {
+    /// Never synchronize the source song
+    Skip,
+    /// ID of the destination song
+    Song(String),
+}
+
+impl OverrideTarget {
+    /// Destination song forced by the override, None if the source song is skipped
+    pub fn resolve(&self, src_song: &Song, dst_type: MusicApiType) -> Option<Song> {
+        let Self::Song(id) = self else {
+            return None;
+        };
+        // only the ID is used to add the song, the source metadata is kept for display
+        Some(Song {
+            source: dst_type,
+            id: id.clone(),
+            sid: None,
+            isrc: None,
+            ..src_song.clone()
+        })
+    }
+}
+
+impl MatchOverride {
+    fn matches(&self, src_song: &Song) -> bool {
+        if let (Some(source), Some(id)) = (&self.source, &self.id) {
+            return *source == src_song.source && *id == src_song.id;
+        }
+        if let (Some(isrc), Some(src_isrc)) = (&self.isrc, &src_song.isrc) {
+            return isrc.eq_ignore_ascii_case(src_isrc);
+        }
+        false
+    }
+}
+
+impl Overrides {
+    pub fn load(path: &Path) -> Result<Self> {
+        let overrides: Self = serde_json::from_reader(std::fs::File::open(path)?)?;
+        for o in &overrides.overrides {
+            if (o.source.is_none() || o.id.is_none()) && o.isrc.is_none() {
+                return Err(eyre!(
+                    "invalid override {:?}: a source platform and ID, or an ISRC is required",
+                    o
+                ));
+            }
+        }
+        Ok(overrides)
+    }
+
+    pub fn load_opt(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_config(config: &ConfigArgs) -> Result<Self> {
+        match &config.overrides {
+            // the file is created by the interactive review
+            Some(path) if config.interactive && !path.exists() => Ok(Self::default()),
+            path => Self::load_opt(path.as_deref()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add an override by platform and ID, replacing the previous one of the source song
+    pub fn add(&mut self, src_song: &Song, dst_type: MusicApiType, target: OverrideTarget) {
+        self.overrides.retain(|o| {
+            o.destination != dst_type
+                || o.source.as_ref() != Some(&src_song.source)
+                || o.id.as_ref() != Some(&src_song.id)
+        });
+        self.overrides.push(MatchOverride {
+            source: Some(src_song.source.clone()),
+            id: Some(src_song.id.clone()),
+            isrc: src_song.isrc.clone(),
+            destination: dst_type,
+            target,
+        });
+    }
+
+    /// Override of the source song on the destination platform, overrides by platform and
+    /// ID take precedence over the ones by ISRC
+    pub fn get(&self, src_song: &Song, dst_type: &MusicApiType) -> Option<&OverrideTarget> {
+        let mut candidates = self
+            .overrides
+            .iter()
+            .filter(|o| o.destination == *dst_type && o.matches(src_song));
+        let first = candidates.next()?;
+        if first.id.is_some() {
+            return Some(&first.target);
+        }
+        let by_id = candidates.find(|o| o.source.is_some() && o.id.is_some());
+        Some(&by_id.unwrap_or(first).target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_get() {
+        let overrides: Overrides = serde_json::from_str(
+            r#"{
+                "overrides": [
+                    { "isrc": "USUM71703861", "destination": "Tidal", "target": "skip" },
+                    { "source": "Spotify", "id": "abc", "destination": "Tidal", "target": { "song": "123" } },
+                    { "isrc": "usum71703861", "destination": "YtMusic", "target": { "song": "yt_id" } }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut song = Song::test(MusicApiType::Spotify, "abc", "Song").with_isrc("USUM71703861");
+
+        let target = overrides.get(&song, &MusicApiType::Tidal);
+        assert_eq!(target, Some(&OverrideTarget::Song("123".to_string())));
+        let target = overrides.get(&song, &MusicApiType::YtMusic);
+        assert_eq!(target, Some(&OverrideTarget::Song("yt_id".to_string())));
+        assert_eq!(overrides.get(&song, &MusicApiType::Spotify), None);
+
+        song.id = "other".to_string();
+        let target = overrides.get(&song, &MusicApiType::Tidal);
+        assert_eq!(target, Some(&OverrideTarget::Skip));
+    }
+}
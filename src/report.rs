@@ -0,0 +1,374 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use color_eyre::eyre::Result;
+use serde::Serialize;
+
+use crate::music_api::MusicApiType;
+use crate::sync::SyncSummary;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// Format matching the file extension, JSON by default
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("md" | "markdown") => Self::Markdown,
+            Some("html" | "htm") => Self::Html,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// Outcome of a whole synchronization run
+#[derive(Serialize, Debug)]
+pub struct SyncReport {
+    /// Source platform, None when importing an exported JSON file
+    pub source: Option<MusicApiType>,
+    /// UNIX timestamp of the start of the run, in seconds
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub destinations: Vec<DestinationReport>,
+    pub playlists: Vec<PlaylistReport>,
+    /// Errors not scoped to a playlist
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DestinationReport {
+    /// Index of the destination in the destinations chain
+    pub index: usize,
+    pub platform: MusicApiType,
+    pub summary: SyncSummary,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PlaylistReport {
+    /// Index of the destination in the destinations chain
+    pub destination_index: usize,
+    pub destination: MusicApiType,
+    /// Name of the source playlist
+    pub name: String,
+    /// Name of the destination playlist, after mapping
+    pub destination_name: String,
+    /// Whether the destination playlist was created by this run
+    pub created: bool,
+    /// Songs added to the destination playlist
+    pub added: usize,
+    /// Songs already in the destination playlist, skipped by an override or lacking
+    /// album metadata
+    pub skipped: usize,
+    /// Duplicated songs in the source playlist, or resolving to the same destination song
+    pub duplicates: usize,
+    /// Source songs without a match on the destination platform
+    pub missing: Vec<String>,
+    pub isrc_matches: u32,
+    pub fuzzy_matches: u32,
+    pub manual_matches: u32,
+    pub duration_ms: u64,
+    pub errors: Vec<String>,
+}
+
+impl PlaylistReport {
+    pub fn new(
+        destination_index: usize,
+        destination: MusicApiType,
+        name: &str,
+        destination_name: &str,
+    ) -> Self {
+        Self {
+            destination_index,
+            destination,
+            name: name.to_string(),
+            destination_name: destination_name.to_string(),
+            created: false,
+            added: 0,
+            skipped: 0,
+            duplicates: 0,
+            missing: vec![],
+            isrc_matches: 0,
+            fuzzy_matches: 0,
+            manual_matches: 0,
+            duration_ms: 0,
+            errors: vec![],
+        }
+    }
+}
+
+fn format_duration(duration_ms: u64) -> String {
+    format!("{:.1}s", duration_ms as f64 / 1000.0)
+}
+
+fn escape_markdown(s: &str) -> String {
+    s.replace('|', "\\|")
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl SyncReport {
+    pub fn new(source: Option<MusicApiType>) -> Self {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            source,
+            started_at,
+            duration_ms: 0,
+            destinations: vec![],
+            playlists: vec![],
+            errors: vec![],
+        }
+    }
+
+    fn source_name(&self) -> String {
+        self.source.as_ref().map_or_else(
+            || "an exported JSON file".to_string(),
+            |s| format!("{:?}", s),
+        )
+    }
+
+    fn destination_playlists(&self, index: usize) -> impl Iterator<Item = &PlaylistReport> {
+        self.playlists
+            .iter()
+            .filter(move |p| p.destination_index == index)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# SyncDisBoi report\n");
+        let _ = writeln!(
+            out,
+            "Synchronization from {}, took {}.",
+            self.source_name(),
+            format_duration(self.duration_ms)
+        );
+        for dst in &self.destinations {
+            let _ = writeln!(out, "\n## {:?}\n", dst.platform);
+            let _ = writeln!(
+                out,
+                "{}, took {}.\n",
+                dst.summary,
+                format_duration(dst.duration_ms)
+            );
+            let _ = writeln!(
+                out,
+                "| Playlist | Created | Added | Skipped | Missing | Duplicates | ISRC | Fuzzy | Manual | Time | Errors |"
+            );
+            let _ = writeln!(out, "|---|---|---|---|---|---|---|---|---|---|---|");
+            for p in self.destination_playlists(dst.index) {
+                let name = if p.name == p.destination_name {
+                    escape_markdown(&p.name)
+                } else {
+                    format!(
+                        "{} → {}",
+                        escape_markdown(&p.name),
+                        escape_markdown(&p.destination_name)
+                    )
+                };
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+                    name,
+                    if p.created { "yes" } else { "no" },
+                    p.added,
+                    p.skipped,
+                    p.missing.len(),
+                    p.duplicates,
+                    p.isrc_matches,
+                    p.fuzzy_matches,
+                    p.manual_matches,
+                    format_duration(p.duration_ms),
+                    p.errors.len()
+                );
+            }
+            for p in self.destination_playlists(dst.index) {
+                if p.missing.is_empty() && p.errors.is_empty() {
+                    continue;
+                }
+                let _ = writeln!(out, "\n### {}\n", p.name);
+                for song in &p.missing {
+                    let _ = writeln!(out, "- missing: {}", song);
+                }
+                for error in &p.errors {
+                    let _ = writeln!(out, "- error: {}", error);
+                }
+            }
+        }
+        if !self.errors.is_empty() {
+            let _ = writeln!(out, "\n## Errors\n");
+            for error in &self.errors {
+                let _ = writeln!(out, "- {}", error);
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>SyncDisBoi report</title>\n<style>\n\
+            body {{ font-family: sans-serif; margin: 2em; }}\n\
+            table {{ border-collapse: collapse; }}\n\
+            th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }}\n\
+            th {{ background: #eee; }}\n\
+            .error {{ color: #b00; }}\n\
+            </style>\n</head>\n<body>\n<h1>SyncDisBoi report</h1>"
+        );
+        let _ = writeln!(
+            out,
+            "<p>Synchronization from {}, took {}.</p>",
+            self.source_name(),
+            format_duration(self.duration_ms)
+        );
+        for dst in &self.destinations {
+            let _ = writeln!(out, "<h2>{:?}</h2>", dst.platform);
+            let _ = writeln!(
+                out,
+                "<p>{}, took {}.</p>",
+                escape_html(&dst.summary.to_string()),
+                format_duration(dst.duration_ms)
+            );
+            let _ = writeln!(
+                out,
+                "<table>\n<tr><th>Playlist</th><th>Created</th><th>Added</th><th>Skipped</th>\
+                <th>Missing</th><th>Duplicates</th><th>ISRC</th><th>Fuzzy</th><th>Manual</th>\
+                <th>Time</th><th>Errors</th></tr>"
+            );
+            for p in self.destination_playlists(dst.index) {
+                let mut name = escape_html(&p.name);
+                if p.name != p.destination_name {
+                    let _ = write!(name, " → {}", escape_html(&p.destination_name));
+                }
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                    <td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    name,
+                    if p.created { "yes" } else { "no" },
+                    p.added,
+                    p.skipped,
+                    p.missing.len(),
+                    p.duplicates,
+                    p.isrc_matches,
+                    p.fuzzy_matches,
+                    p.manual_matches,
+                    format_duration(p.duration_ms),
+                    p.errors.len()
+                );
+            }
+            let _ = writeln!(out, "</table>");
+            for p in self.destination_playlists(dst.index) {
+                if p.missing.is_empty() && p.errors.is_empty() {
+                    continue;
+                }
+                let _ = writeln!(out, "<h3>{}</h3>\n<ul>", escape_html(&p.name));
+                for song in &p.missing {
+                    let _ = writeln!(out, "<li>missing: {}</li>", escape_html(song));
+                }
+                for error in &p.errors {
+                    let _ = writeln!(out, "<li class=\"error\">{}</li>", escape_html(error));
+                }
+                let _ = writeln!(out, "</ul>");
+            }
+        }
+        if !self.errors.is_empty() {
+            let _ = writeln!(out, "<h2>Errors</h2>\n<ul>");
+            for error in &self.errors {
+                let _ = writeln!(out, "<li class=\"error\">{}</li>", escape_html(error));
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+        let _ = writeln!(out, "</body>\n</html>");
+        out
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        Ok(match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)?,
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        })
+    }
+
+    pub fn save(&self, path: &Path, format: Option<ReportFormat>) -> Result<()> {
+        let format = format.unwrap_or_else(|| ReportFormat::from_path(path));
+        std::fs::write(path, self.render(format)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_render() {
+        let mut report = SyncReport::new(Some(MusicApiType::Spotify));
+        report.destinations.push(DestinationReport {
+            index: 0,
+            platform: MusicApiType::Tidal,
+            summary: SyncSummary::default(),
+            duration_ms: 1500,
+        });
+        let mut playlist = PlaylistReport::new(0, MusicApiType::Tidal, "Rock | Roll", "Rock <3");
+        playlist.added = 2;
+        playlist.missing.push("Song - Artist".to_string());
+        report.playlists.push(playlist);
+
+        let markdown = report.render(ReportFormat::Markdown).unwrap();
+        assert!(markdown.contains("| Rock \\| Roll → Rock <3 | no | 2 | 0 | 1 |"));
+        assert!(markdown.contains("- missing: Song - Artist"));
+        let html = report.render(ReportFormat::Html).unwrap();
+        assert!(html.contains("<td>Rock | Roll → Rock &lt;3</td>"));
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["playlists"][0]["added"], 2);
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.md")),
+            ReportFormat::Markdown
+        );
+    }
+
+    #[test]
+    fn test_report_same_platform_destinations() {
+        let mut report = SyncReport::new(Some(MusicApiType::Spotify));
+        for index in 0..2 {
+            report.destinations.push(DestinationReport {
+                index,
+                platform: MusicApiType::Tidal,
+                summary: SyncSummary::default(),
+                duration_ms: 0,
+            });
+            let name = format!("Playlist {}", index);
+            report.playlists.push(PlaylistReport::new(
+                index,
+                MusicApiType::Tidal,
+                &name,
+                &name,
+            ));
+        }
+        let markdown = report.render(ReportFormat::Markdown).unwrap();
+        // each playlist is listed under its own destination only
+        assert_eq!(markdown.matches("| Playlist 0 |").count(), 1);
+        assert_eq!(markdown.matches("| Playlist 1 |").count(), 1);
+        let second = markdown.rfind("## Tidal").unwrap();
+        assert!(markdown[second..].contains("| Playlist 1 |"));
+        assert!(!markdown[second..].contains("| Playlist 0 |"));
+    }
+}
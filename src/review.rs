@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+use dialoguer::{Input, Select};
+use tracing::info;
+
+use crate::matcher::Matcher;
+use crate::music_api::{DynMusicApi, Song};
+use crate::overrides::{OverrideTarget, Overrides};
+
+const REVIEW_CANDIDATES: usize = 5;
+
+fn format_duration(duration_ms: usize) -> String {
+    let secs = duration_ms / 1000;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Let the user pick the destination song of an unconfident or missing match, the
+/// decision is saved to the overrides file so later runs reuse it
+pub async fn review_match(
+    src_song: &Song,
+    found: Option<Song>,
+    dst_api: &DynMusicApi,
+    overrides: &mut Overrides,
+    overrides_path: &Path,
+) -> Result<Option<Song>> {
+    let mut candidates: Vec<Song> = found.into_iter().collect();
+    if let Some(query) = src_song.build_queries().pop() {
+        for candidate in dst_api.search_query(&query, REVIEW_CANDIDATES).await? {
+            if !candidates.iter().any(|c| c.id == candidate.id) {
+                candidates.push(candidate);
+            }
+        }
+    }
+
+    let dst_type = dst_api.api_type();
+    let (target, dst_song) = loop {
+        let mut items: Vec<String> = candidates
+            .iter()
+            .map(|c| {
+                let score = dst_api.matcher().score(src_song, c);
+                format!(
+                    "{} [{}] ({:.0}%)",
+                    c,
+                    format_duration(c.duration_ms),
+                    score.confidence * 100.0
+                )
+            })
+            .collect();
+        items.push("Skip this song".to_string());
+        items.push("Search with another query".to_string());
+        items.push("Enter a song URL or ID".to_string());
+
+        let choice = Select::new()
+            .with_prompt(format!(
+                "Destination song on {:?} for \"{}\" [{}]",
+                dst_type,
+                src_song,
+                format_duration(src_song.duration_ms)
+            ))
+            .items(&items)
+            .default(0)
+            .interact()?;
+
+        match choice.checked_sub(candidates.len()) {
+            None => {
+                let dst_song = candidates.swap_remove(choice);
+                break (OverrideTarget::Song(dst_song.id.clone()), Some(dst_song));
+            }
+            Some(0) => break (OverrideTarget::Skip, None),
+            Some(1) => {
+                let query: String = Input::new()
+                    .with_prompt("Search query")
+                    .with_initial_text(src_song.clean_name())
+                    .interact_text()?;
+                candidates = dst_api.search_query(&query, REVIEW_CANDIDATES).await?;
+            }
+            Some(_) => {
+                let input: String = Input::new().with_prompt("Song URL or ID").interact_text()?;
+                let id = dst_type
+                    .parse_song_url(&input)
+                    .unwrap_or_else(|| input.trim().to_string());
+                let target = OverrideTarget::Song(id);
+                let dst_song = target.resolve(src_song, dst_type.clone());
+                break (target, dst_song);
+            }
+        }
+    };
+
+    overrides.add(src_song, dst_type, target);
+    overrides.save(overrides_path)?;
+    info!("decision saved to {}", overrides_path.display());
+    Ok(dst_song)
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::ConfigArgs;
+use crate::matcher::{MatchPath, MatchThresholds};
+use crate::music_api::{DynMusicApi, MusicApiType, Song};
+
+/// Search results of the previous runs, stored on disk
+#[derive(Deserialize, Serialize, Debug, Default)]
+struct SearchCacheFile {
+    /// Thresholds the results were matched with, the cache is discarded when they change
+    thresholds: Option<MatchThresholds>,
+    /// Number of search results compared per query, the cache is discarded when it changes
+    #[serde(default)]
+    search_candidates: Option<usize>,
+    entries: HashMap<String, SearchCacheEntry>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SearchCacheEntry {
+    /// Matched destination song, None when no match was found
+    pub song: Option<(Song, MatchPath)>,
+    /// UNIX timestamp of the search, in seconds
+    pub timestamp: u64,
+}
+
+/// Cache of the destination songs matching the source songs, keyed by destination
+/// platform, country and source song
+pub struct SearchCache {
+    path: Option<PathBuf>,
+    file: SearchCacheFile,
+    /// Seconds after which a negative result is searched again
+    negative_ttl: u64,
+    /// Number of results inserted since the last save
+    unsaved: usize,
+}
+
+impl SearchCache {
+    const SAVE_INTERVAL: usize = 50;
+
+    /// Disabled cache, every song is searched
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            file: SearchCacheFile::default(),
+            negative_ttl: 0,
+            unsaved: 0,
+        }
+    }
+
+    pub fn from_config(config: &ConfigArgs) -> Result<Self> {
+        let Some(path) = config.search_cache.clone() else {
+            return Ok(Self::disabled());
+        };
+        if config.no_search_cache {
+            return Ok(Self::disabled());
+        }
+        if config.clear_search_cache && path.exists() {
+            info!("clearing search cache {}", path.display());
+            std::fs::remove_file(&path)?;
+        }
+
+        let thresholds = MatchThresholds::from_config(config);
+        let mut file = SearchCacheFile::default();
+        if path.exists() {
+            match serde_json::from_reader::<_, SearchCacheFile>(std::fs::File::open(&path)?) {
+                Ok(cached)
+                    if cached.thresholds == Some(thresholds)
+                        && cached.search_candidates == Some(config.search_candidates) =>
+                {
+                    file = cached;
+                }
+                Ok(_) => info!("matching settings changed, discarding the search cache"),
+                Err(e) => warn!("invalid search cache, discarding it: {}", e),
+            }
+        }
+        file.thresholds = Some(thresholds);
+        file.search_candidates = Some(config.search_candidates);
+
+        Ok(Self {
+            path: Some(path),
+            file,
+            negative_ttl: config.search_cache_ttl * 24 * 60 * 60,
+            unsaved: 0,
+        })
+    }
+
+    fn key(src_song: &Song, dst_type: &MusicApiType, dst_country: &str) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            dst_type.short_name(),
+            dst_country,
+            src_song.source.short_name(),
+            src_song.id
+        )
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+
+    /// Cached search result of the source song, None on a cache miss
+    pub fn get(&self, src_song: &Song, dst_api: &DynMusicApi) -> Option<&SearchCacheEntry> {
+        self.path.as_ref()?;
+        let key = Self::key(src_song, &dst_api.api_type(), dst_api.country_code());
+        let entry = self.lookup(&key, Self::now())?;
+        debug!("search cache hit for song: {}", src_song);
+        Some(entry)
+    }
+
+    fn lookup(&self, key: &str, now: u64) -> Option<&SearchCacheEntry> {
+        let entry = self.file.entries.get(key)?;
+        // negative results expire, as the song might have been released since
+        if entry.song.is_none() && now.saturating_sub(entry.timestamp) > self.negative_ttl {
+            return None;
+        }
+        Some(entry)
+    }
+
+    pub fn insert(
+        &mut self,
+        src_song: &Song,
+        dst_api: &DynMusicApi,
+        dst_song: Option<(Song, MatchPath)>,
+    ) -> Result<()> {
+        if self.path.is_none() {
+            return Ok(());
+        }
+        let entry = SearchCacheEntry {
+            song: dst_song,
+            timestamp: Self::now(),
+        };
+        let key = Self::key(src_song, &dst_api.api_type(), dst_api.country_code());
+        self.file.entries.insert(key, entry);
+        self.unsaved += 1;
+        if self.unsaved >= Self::SAVE_INTERVAL {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(path) = &self.path
+            && self.unsaved > 0
+        {
+            std::fs::write(path, serde_json::to_string(&self.file)?)?;
+            self.unsaved = 0;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_cache_lookup() {
+        let song = Song::test(MusicApiType::Spotify, "abc", "Song");
+        let mut cache = SearchCache {
+            negative_ttl: 100,
+            ..SearchCache::disabled()
+        };
+        let found = SearchCache::key(&song, &MusicApiType::Tidal, "US");
+        let missing = SearchCache::key(&song, &MusicApiType::Tidal, "FR");
+        assert_ne!(found, missing);
+        cache.file.entries.insert(
+            found.clone(),
+            SearchCacheEntry {
+                song: Some((song.clone(), MatchPath::Fuzzy)),
+                timestamp: 0,
+            },
+        );
+        cache.file.entries.insert(
+            missing.clone(),
+            SearchCacheEntry {
+                song: None,
+                timestamp: 1000,
+            },
+        );
+
+        let res = cache.lookup(&found, 10_000).unwrap().song.clone();
+        assert_eq!(
+            res.map(|(s, p)| (s.id, p)),
+            Some(("abc".to_string(), MatchPath::Fuzzy))
+        );
+        assert!(cache.lookup(&missing, 1050).unwrap().song.is_none());
+        assert!(cache.lookup(&missing, 1200).is_none());
+        assert!(cache.lookup("tidal:US:spotify:other", 0).is_none());
+    }
+}
@@ -7,6 +7,8 @@ use std::time::Duration;
 
 use async_recursion::async_recursion;
 use async_trait::async_trait;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
 use color_eyre::eyre::{Result, eyre};
 use model::SpotifyUserResponse;
 use reqwest::header::HeaderMap;
@@ -17,15 +19,18 @@ use tokio::net::TcpListener;
 use tracing::{debug, info, warn};
 
 use self::model::{
-    SpotifyPageResponse, SpotifyPlaylistResponse, SpotifySnapshotResponse, SpotifySongItemResponse,
+    SpotifyAlbumSearchResponse, SpotifyArtistSearchResponse, SpotifyFollowedArtistsResponse,
+    SpotifyPageResponse, SpotifyPlaylistResponse, SpotifySavedAlbumResponse,
+    SpotifySnapshotResponse, SpotifySongItemResponse, SpotifyTopTracksResponse,
 };
 use crate::ConfigArgs;
+use crate::matcher::{MatchPath, Matcher};
 use crate::music_api::{
-    MusicApi, MusicApiType, OAuthRefreshToken, OAuthToken, PLAYLIST_DESC, Playlist, Playlists,
-    Song, Songs,
+    Album, Albums, Artist, Artists, MusicApi, MusicApiType, OAuthRefreshToken, OAuthToken,
+    Playlist, Playlists, Song, Songs,
 };
 use crate::spotify::model::SpotifySearchResponse;
-use crate::utils::debug_response_json;
+use crate::utils::{client_builder, debug_response_json, format_timestamp};
 
 pub struct SpotifyApi {
     client: reqwest::Client,
@@ -51,13 +56,18 @@ impl SpotifyApi {
         "user-read-private",
         "user-library-read",
         "user-library-modify",
+        "user-follow-read",
+        "user-follow-modify",
         "playlist-read-collaborative",
         "playlist-modify-public",
         "playlist-read-private",
         "playlist-modify-private",
+        "ugc-image-upload",
     ];
     const LISTEN_RESPONSE: &'static str = "HTTP/1.1 200 OK\r\nContent-Length: 56\r\n\r\nAuthorization code received! You may now close this tab.";
     const RES_DEBUG_FILENAME: &'static str = MusicApiType::Spotify.short_name();
+    // NOTE: the limit applies to the base64 encoded image
+    const MAX_COVER_SIZE: usize = 256 * 1024;
 
     pub async fn new(
         client_id: &str,
@@ -81,17 +91,7 @@ impl SpotifyApi {
         headers.insert("authorization", bearer.parse()?);
         headers.insert("content-type", "application/json".parse()?);
 
-        let mut client = reqwest::ClientBuilder::new()
-            .default_headers(headers)
-            .cookie_store(true);
-
-        if let Some(proxy) = &config.proxy {
-            client = client
-                .proxy(reqwest::Proxy::all(proxy)?)
-                .danger_accept_invalid_certs(true);
-        }
-
-        let client = client.build()?;
+        let client = client_builder(&config)?.default_headers(headers).build()?;
 
         let mut spotify_api = Self {
             client,
@@ -298,16 +298,25 @@ impl MusicApi for SpotifyApi {
         MusicApiType::Spotify
     }
 
+    fn config(&self) -> &ConfigArgs {
+        &self.config
+    }
+
     fn country_code(&self) -> &str {
         &self.country_code
     }
 
-    async fn create_playlist(&self, name: &str, public: bool) -> Result<Playlist> {
+    async fn create_playlist(
+        &self,
+        name: &str,
+        description: &str,
+        public: bool,
+    ) -> Result<Playlist> {
         let path = "/me/playlists";
         let body = json!({
             "name": name,
             "public": public,
-            "description": PLAYLIST_DESC,
+            "description": description,
         });
         let res: SpotifyPlaylistResponse = self
             .make_request_json(path, &HttpMethod::Post(&body), 50, 0)
@@ -391,81 +400,159 @@ impl MusicApi for SpotifyApi {
         Ok(())
     }
 
-    async fn search_song(&self, song: &Song) -> Result<Option<Song>> {
-        let path = "/search";
-        let max_len = 100;
-        let mut queries = vec![];
+    async fn update_playlist_details(
+        &self,
+        playlist: &mut Playlist,
+        description: &str,
+        public: bool,
+    ) -> Result<()> {
+        let path = format!("/playlists/{}", playlist.id);
+        let body = json!({
+            "public": public,
+            "description": description,
+        });
+        self.make_request_json::<()>(&path, &HttpMethod::Put(&body), 50, 0)
+            .await?;
+        playlist.description = Some(description.to_string());
+        playlist.public = Some(public);
+        Ok(())
+    }
+
+    async fn upload_playlist_cover(&self, playlist: &mut Playlist, jpeg: &[u8]) -> Result<()> {
+        let image = BASE64_STANDARD.encode(jpeg);
+        if image.len() > Self::MAX_COVER_SIZE {
+            return Err(eyre!(
+                "cover image is bigger than spotify max size: {} bytes",
+                image.len()
+            ));
+        }
+        let endpoint = Self::build_endpoint(&format!("/playlists/{}/images", playlist.id));
+        let res = self
+            .client
+            .put(endpoint)
+            .header("content-type", "image/jpeg")
+            .body(image)
+            .send()
+            .await?;
+        let status = res.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            self.api_rate_wait(&res).await?;
+            return self.upload_playlist_cover(playlist, jpeg).await;
+        }
+        if !status.is_success() {
+            return Err(eyre!("Invalid HTTP status: {}", status));
+        }
+        Ok(())
+    }
 
+    async fn search_song_match(&self, song: &Song) -> Result<Option<(Song, MatchPath)>> {
         if let Some(isrc) = &song.isrc {
-            queries.push(format!("isrc:{}", isrc));
-        } else {
-            let mut track_query = format!("track:\"{}\"", song.clean_name());
-            if track_query.len() > max_len {
-                warn!(
-                    "song name is bigger than spotify max search: \"{}\", truncating",
-                    track_query
-                );
-                // Not the best solution, but it's worth a try
-                track_query = track_query[..max_len].to_string();
+            if let Some(res_song) = self.search_isrc(song, isrc).await? {
+                return Ok(Some((res_song, MatchPath::Isrc)));
             }
+            debug!("no ISRC match, falling back to fuzzy search: {}", song);
+        }
+        let max_len = 100;
+        let mut queries = vec![];
+        let mut track_query = format!("track:\"{}\"", song.clean_name());
+        if track_query.len() > max_len {
+            warn!(
+                "song name is bigger than spotify max search: \"{}\", truncating",
+                track_query
+            );
+            // Not the best solution, but it's worth a try
+            track_query = track_query[..max_len].to_string();
+        }
 
-            let artist_queries: Vec<String> = song
-                .artists
-                .iter()
-                .map(|a| format!("artist:\"{}\"", a.clean_name()))
-                .collect();
+        let artist_queries: Vec<String> = song
+            .artists
+            .iter()
+            .map(|a| format!("artist:\"{}\"", a.clean_name()))
+            .collect();
 
-            let mut album_query = None;
-            if let Some(album) = &song.album {
-                album_query = Some(format!("album:\"{}\"", album.clean_name()));
-            }
+        let mut album_query = None;
+        if let Some(album) = &song.album {
+            album_query = Some(format!("album:\"{}\"", album.clean_name()));
+        }
 
-            // Query: Track + Album
-            if let Some(album_query) = album_query.as_ref() {
-                let tr_al_query = format!("{} {}", track_query, album_query);
-                push_query(&mut queries, tr_al_query, max_len);
-            }
-            // Query: Track + Artist
+        // Query: Track + Album
+        if let Some(album_query) = album_query.as_ref() {
+            let tr_al_query = format!("{} {}", track_query, album_query);
+            push_query(&mut queries, tr_al_query, max_len);
+        }
+        // Query: Track + Artist
+        for artist_query in artist_queries.iter().rev() {
+            // INFO: spotify doesn't support multiple artists in search
+            // we have to create one query per artist
+            let tr_ar_query = format!("{} {}", track_query, artist_query);
+            push_query(&mut queries, tr_ar_query, max_len);
+        }
+        // Query: Track + Artist + Album
+        if let Some(album_query) = album_query.as_ref() {
             for artist_query in artist_queries.iter().rev() {
-                // INFO: spotify doesn't support multiple artists in search
-                // we have to create one query per artist
-                let tr_ar_query = format!("{} {}", track_query, artist_query);
-                push_query(&mut queries, tr_ar_query, max_len);
-            }
-            // Query: Track + Artist + Album
-            if let Some(album_query) = album_query.as_ref() {
-                for artist_query in artist_queries.iter().rev() {
-                    let tr_ar_al_query =
-                        format!("{} {} {}", track_query, artist_query, album_query);
-                    push_query(&mut queries, tr_ar_al_query, max_len);
-                }
+                let tr_ar_al_query = format!("{} {} {}", track_query, artist_query, album_query);
+                push_query(&mut queries, tr_ar_al_query, max_len);
             }
         }
 
-        while let Some(query) = queries.pop() {
-            let get_params = [("type", "track"), ("q", &query)];
-            let res: SpotifySearchResponse = self
-                .make_request_json(path, &HttpMethod::Get(&get_params), 3, 0)
+        self.search_fuzzy(song, queries).await
+    }
+
+    async fn search_isrc(&self, song: &Song, isrc: &str) -> Result<Option<Song>> {
+        let candidates = self.search_query(&format!("isrc:{}", isrc), 3).await?;
+        Ok(self
+            .matcher()
+            .best_match(song, candidates)
+            .map(|(res_song, _)| res_song))
+    }
+
+    async fn search_query(&self, query: &str, limit: usize) -> Result<Vec<Song>> {
+        // NOTE: A maximum of 50 results can be requested at once
+        let limit = limit.min(50);
+        let get_params = [("type", "track"), ("q", query)];
+        let res: SpotifySearchResponse = self
+            .make_request_json("/search", &HttpMethod::Get(&get_params), limit, 0)
+            .await?;
+        let res_songs: Songs = res.try_into()?;
+        Ok(res_songs.0.into_iter().take(limit).collect())
+    }
+
+    async fn add_likes(&self, songs: &[Song]) -> Result<()> {
+        // NOTE: A maximum of 50 items can be specified in one request
+        for songs_chunk in songs.chunks(50) {
+            // keep the source timestamps when all of them are known, ids and
+            // timestamped_ids cannot be mixed
+            let body = if songs_chunk.iter().all(|s| s.added_at.is_some()) {
+                let items: Vec<_> = songs_chunk
+                    .iter()
+                    .filter_map(|s| {
+                        let added_at = format_timestamp(s.added_at?);
+                        Some(json!({ "id": s.id, "added_at": added_at }))
+                    })
+                    .collect();
+                json!({
+                    "timestamped_ids": items,
+                })
+            } else {
+                let ids: Vec<&str> = songs_chunk.iter().map(|s| s.id.as_str()).collect();
+                json!({
+                    "ids": ids,
+                })
+            };
+            self.make_request_json::<()>("/me/tracks", &HttpMethod::Put(&body), 50, 0)
                 .await?;
-            let res_songs: Songs = res.try_into()?;
-            // iterate over top 3 results
-            for res_song in res_songs.0.into_iter().take(3) {
-                if song.compare(&res_song) {
-                    return Ok(Some(res_song));
-                }
-            }
         }
-        return Ok(None);
+        Ok(())
     }
 
-    async fn add_likes(&self, songs: &[Song]) -> Result<()> {
+    async fn remove_likes(&self, songs: &[Song]) -> Result<()> {
         // NOTE: A maximum of 50 items can be specified in one request
         for songs_chunk in songs.chunks(50) {
             let ids: Vec<&str> = songs_chunk.iter().map(|s| s.id.as_str()).collect();
             let body = json!({
                 "ids": ids,
             });
-            self.make_request_json::<()>("/me/tracks", &HttpMethod::Put(&body), 50, 0)
+            self.make_request_json::<()>("/me/tracks", &HttpMethod::Delete(&body), 50, 0)
                 .await?;
         }
         Ok(())
@@ -478,17 +565,144 @@ impl MusicApi for SpotifyApi {
         let songs: Songs = res.try_into()?;
         Ok(songs.0)
     }
+
+    async fn get_saved_albums(&self) -> Result<Vec<Album>> {
+        let res: SpotifyPageResponse<SpotifySavedAlbumResponse> = self
+            .paginated_request("/me/albums", HttpMethod::Get(&[]), 50)
+            .await?;
+        let albums: Albums = res.try_into()?;
+        Ok(albums.0)
+    }
+
+    async fn search_album(&self, album: &Album) -> Result<Option<Album>> {
+        let path = "/search";
+        let max_len = 100;
+        let mut queries = vec![];
+
+        let album_query = format!("album:\"{}\"", album.clean_name());
+        push_query(&mut queries, album_query.clone(), max_len);
+        for artist in album.artists.iter().rev() {
+            let al_ar_query = format!("{} artist:\"{}\"", album_query, artist.clean_name());
+            push_query(&mut queries, al_ar_query, max_len);
+        }
+        // the UPC query is the first one to be tried
+        if let Some(upc) = &album.upc {
+            queries.push(format!("upc:{}", upc));
+        }
+
+        while let Some(query) = queries.pop() {
+            let upc_query = query.starts_with("upc:");
+            let get_params = [("type", "album"), ("q", &query)];
+            let res: SpotifyAlbumSearchResponse = self
+                .make_request_json(path, &HttpMethod::Get(&get_params), 3, 0)
+                .await?;
+            let res_albums: Albums = res.try_into()?;
+            // iterate over top 3 results
+            for mut res_album in res_albums.0.into_iter().take(3) {
+                // search results don't contain the UPC
+                if upc_query {
+                    res_album.upc.clone_from(&album.upc);
+                    return Ok(Some(res_album));
+                }
+                if album.compare(&res_album) {
+                    return Ok(Some(res_album));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn add_saved_albums(&self, albums: &[Album]) -> Result<()> {
+        // NOTE: A maximum of 20 items can be specified in one request
+        let ids: Vec<&str> = albums.iter().filter_map(|a| a.id.as_deref()).collect();
+        for ids_chunk in ids.chunks(20) {
+            let body = json!({
+                "ids": ids_chunk,
+            });
+            self.make_request_json::<()>("/me/albums", &HttpMethod::Put(&body), 50, 0)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_followed_artists(&self) -> Result<Vec<Artist>> {
+        let mut artists = vec![];
+        let mut after: Option<String> = None;
+        // NOTE: the followed artists are paginated with a cursor instead of an offset
+        loop {
+            let mut get_params = vec![("type", "artist")];
+            if let Some(after) = &after {
+                get_params.push(("after", after));
+            }
+            let res: SpotifyFollowedArtistsResponse = self
+                .make_request_json("/me/following", &HttpMethod::Get(&get_params), 50, 0)
+                .await?;
+            after = res.artists.cursors.as_ref().and_then(|c| c.after.clone());
+            let res_artists: Artists = res.try_into()?;
+            artists.extend(res_artists.0);
+            if after.is_none() {
+                break;
+            }
+        }
+        Ok(artists)
+    }
+
+    async fn search_artist_query(&self, query: &str, limit: usize) -> Result<Vec<Artist>> {
+        // NOTE: A maximum of 50 results can be requested at once
+        let limit = limit.min(50);
+        let get_params = [("type", "artist"), ("q", query)];
+        let res: SpotifyArtistSearchResponse = self
+            .make_request_json("/search", &HttpMethod::Get(&get_params), limit, 0)
+            .await?;
+        let res_artists: Artists = res.try_into()?;
+        Ok(res_artists.0.into_iter().take(limit).collect())
+    }
+
+    async fn get_artist_top_songs(&self, artist: &Artist) -> Result<Vec<Song>> {
+        let Some(id) = &artist.id else {
+            return Ok(vec![]);
+        };
+        let path = format!("/artists/{}/top-tracks", id);
+        let get_params = [("market", self.country_code.as_str())];
+        let res: SpotifyTopTracksResponse = self
+            .make_request_json(&path, &HttpMethod::Get(&get_params), 10, 0)
+            .await?;
+        let songs: Songs = res.try_into()?;
+        Ok(songs.0)
+    }
+
+    async fn follow_artists(&self, artists: &[Artist]) -> Result<()> {
+        // NOTE: A maximum of 50 items can be specified in one request
+        let ids: Vec<&str> = artists.iter().filter_map(|a| a.id.as_deref()).collect();
+        for ids_chunk in ids.chunks(50) {
+            let body = json!({
+                "ids": ids_chunk,
+            });
+            self.make_request_json::<()>(
+                "/me/following?type=artist",
+                &HttpMethod::Put(&body),
+                50,
+                0,
+            )
+            .await?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::env;
 
+    use clap::Parser;
+
     use super::*;
     use crate::yt_music::YtMusicApi;
 
     #[tokio::test]
+    #[ignore = "requires live Youtube Music and Spotify credentials"]
     async fn test_spotify_search_from_ytmusic() {
+        let config = ConfigArgs::parse_from(["sync_dis_boi"]);
         let yt_client_id = env::var("YTMUSIC_CLIENT_ID").unwrap();
         let yt_client_secret = env::var("YTMUSIC_CLIENT_SECRET").unwrap();
         let config_dir = dirs::config_dir().unwrap();
@@ -498,7 +712,7 @@ mod tests {
             &yt_client_secret,
             oauth_token_path,
             false,
-            None,
+            config.clone(),
         )
         .await
         .unwrap();
@@ -509,9 +723,16 @@ mod tests {
 
         let spotify_client_id = env::var("SPOTIFY_CLIENT_ID").unwrap();
         let spotify_secret = env::var("SPOTIFY_CLIENT_SECRET").unwrap();
-        let spotify = SpotifyApi::new(&spotify_client_id, &spotify_secret, None)
-            .await
-            .unwrap();
+        let oauth_token_path = config_dir.join("SyncDisBoi").join("spotify_oauth.json");
+        let spotify = SpotifyApi::new(
+            &spotify_client_id,
+            &spotify_secret,
+            oauth_token_path,
+            false,
+            config,
+        )
+        .await
+        .unwrap();
 
         let songs = spotify.search_songs(&songs).await.unwrap();
         let correct_ids = [
@@ -22,6 +22,39 @@ pub struct SpotifySearchResponse {
     pub tracks: SpotifyPageResponse<SpotifySongResponse>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct SpotifyAlbumSearchResponse {
+    pub albums: SpotifyPageResponse<SpotifyFullAlbumResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpotifyArtistSearchResponse {
+    pub artists: SpotifyPageResponse<SpotifyArtistResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpotifyFollowedArtistsResponse {
+    pub artists: SpotifyCursorPageResponse<SpotifyArtistResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpotifyTopTracksResponse {
+    pub tracks: Vec<SpotifySongResponse>,
+}
+
+/// Page of a cursor-based paginated endpoint, the offset is ignored
+#[derive(Deserialize, Debug)]
+pub struct SpotifyCursorPageResponse<T: for<'d> Deserialize<'d>> {
+    #[serde(deserialize_with = "deserialize_non_null_vec")]
+    pub items: Vec<T>,
+    pub cursors: Option<SpotifyCursors>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpotifyCursors {
+    pub after: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct SpotifyPageResponse<T: for<'d> Deserialize<'d>> {
     #[serde(deserialize_with = "deserialize_non_null_vec")]
@@ -51,12 +84,20 @@ where
 pub struct SpotifyPlaylistResponse {
     pub id: String,
     pub name: String,
-    #[allow(dead_code)]
+    pub description: Option<String>,
     pub public: Option<bool>,
+    // images can be null if the playlist has no cover
+    pub images: Option<Vec<SpotifyImageResponse>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpotifyImageResponse {
+    pub url: String,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct SpotifySongItemResponse {
+    pub added_at: Option<String>,
     pub track: Option<SpotifySongResponse>,
 }
 
@@ -69,6 +110,7 @@ pub struct SpotifySongResponse {
     pub artists: Vec<SpotifyArtistResponse>,
     pub album: SpotifyAlbumResponse,
     pub external_ids: SpotifyExternalIdsResponse,
+    pub explicit: Option<bool>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -85,10 +127,23 @@ pub struct SpotifyAlbumResponse {
     pub name: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct SpotifySavedAlbumResponse {
+    pub album: SpotifyFullAlbumResponse,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpotifyFullAlbumResponse {
+    pub id: String,
+    pub name: String,
+    pub artists: Vec<SpotifyArtistResponse>,
+    // external ids are missing from the simplified albums returned by search
+    pub external_ids: Option<SpotifyExternalIdsResponse>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct SpotifyExternalIdsResponse {
     // isrc can be null if the song is now deleted/unavailable
     pub isrc: Option<String>,
-    #[allow(dead_code)]
     pub upc: Option<String>,
 }
@@ -5,12 +5,16 @@ use serde::Deserialize;
 use tracing::{debug, error};
 
 use super::model::{
-    SpotifyPageResponse, SpotifyPlaylistResponse, SpotifySearchResponse, SpotifySongItemResponse,
-    SpotifySongResponse,
+    SpotifyAlbumSearchResponse, SpotifyArtistResponse, SpotifyArtistSearchResponse,
+    SpotifyFollowedArtistsResponse, SpotifyFullAlbumResponse, SpotifyPageResponse,
+    SpotifyPlaylistResponse, SpotifySavedAlbumResponse, SpotifySearchResponse,
+    SpotifySongItemResponse, SpotifySongResponse, SpotifyTopTracksResponse,
 };
 use crate::{
-    music_api::{Album, Artist, MusicApiType, Playlist, Playlists, Song, Songs},
-    utils::clean_isrc,
+    music_api::{
+        Album, Albums, Artist, Artists, MusicApiType, Playlist, Playlists, Song, SongVersion, Songs,
+    },
+    utils::{clean_isrc, clean_upc, parse_timestamp},
 };
 
 // multiples
@@ -72,15 +76,84 @@ where
     }
 }
 
+impl TryInto<Albums> for SpotifyAlbumSearchResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Albums, Self::Error> {
+        self.albums.try_into()
+    }
+}
+
+impl TryInto<Songs> for SpotifyTopTracksResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Songs, Self::Error> {
+        let mut res = vec![];
+        for track in self.tracks {
+            match track.try_into() {
+                Ok(s) => res.push(s),
+                Err(e) => error!("failed to parse song in response, skipping it: {}", e),
+            }
+        }
+        Ok(Songs(res))
+    }
+}
+
+impl TryInto<Artists> for SpotifyArtistSearchResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Artists, Self::Error> {
+        Ok(Artists(parse_artists(self.artists.items)))
+    }
+}
+
+impl TryInto<Artists> for SpotifyFollowedArtistsResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Artists, Self::Error> {
+        Ok(Artists(parse_artists(self.artists.items)))
+    }
+}
+
+impl<T> TryInto<Albums> for SpotifyPageResponse<T>
+where
+    T: TryInto<Album, Error = Error> + for<'d> Deserialize<'d>,
+{
+    type Error = Error;
+
+    fn try_into(self) -> Result<Albums, Self::Error> {
+        let mut res = vec![];
+        for item in self.items {
+            let album = match item.try_into() {
+                Ok(a) => a,
+                Err(e) => {
+                    error!("failed to parse album in response, skipping it: {}", e);
+                    continue;
+                }
+            };
+            res.push(album);
+        }
+        Ok(Albums(res))
+    }
+}
+
 // singles
 
 impl TryInto<Playlist> for SpotifyPlaylistResponse {
     type Error = Error;
 
     fn try_into(self) -> Result<Playlist, Self::Error> {
+        // images are ordered by size, the largest first
+        let cover_url = self
+            .images
+            .and_then(|images| images.into_iter().next())
+            .map(|image| image.url);
         Ok(Playlist {
             id: self.id,
             name: self.name.trim().to_string(),
+            description: self.description.filter(|d| !d.is_empty()),
+            public: self.public,
+            cover_url,
             songs: vec![],
         })
     }
@@ -90,7 +163,9 @@ impl TryInto<Song> for SpotifySongItemResponse {
     type Error = Error;
 
     fn try_into(self) -> Result<Song, Self::Error> {
-        self.track.ok_or_eyre("null track metadata")?.try_into()
+        let mut song: Song = self.track.ok_or_eyre("null track metadata")?.try_into()?;
+        song.added_at = self.added_at.as_deref().and_then(parse_timestamp);
+        Ok(song)
     }
 }
 
@@ -102,22 +177,16 @@ impl TryInto<Song> for SpotifySongResponse {
         // SpotifyPageResponse
         let id = self.id.unwrap_or_default();
 
-        let artists = self
-            .artists
-            .into_iter()
-            .filter_map(|i| {
-                Some(Artist {
-                    id: Some(i.id?),
-                    name: i.name?,
-                })
-            })
-            .collect();
+        let artists = parse_artists(self.artists);
         let album = Album {
             id: self.album.id,
             name: self.album.name,
+            artists: vec![],
+            upc: None,
         };
 
         let isrc = clean_isrc(self.external_ids.isrc);
+        let versions = SongVersion::parse(&self.name);
 
         Ok(Song {
             source: MusicApiType::Spotify,
@@ -128,6 +197,42 @@ impl TryInto<Song> for SpotifySongResponse {
             album: Some(album),
             artists,
             duration_ms: self.duration_ms,
+            versions,
+            explicit: self.explicit,
+            added_at: None,
+        })
+    }
+}
+
+impl TryInto<Album> for SpotifySavedAlbumResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Album, Self::Error> {
+        self.album.try_into()
+    }
+}
+
+impl TryInto<Album> for SpotifyFullAlbumResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Album, Self::Error> {
+        Ok(Album {
+            id: Some(self.id),
+            name: self.name,
+            artists: parse_artists(self.artists),
+            upc: clean_upc(self.external_ids.and_then(|e| e.upc)),
         })
     }
 }
+
+fn parse_artists(artists: Vec<SpotifyArtistResponse>) -> Vec<Artist> {
+    artists
+        .into_iter()
+        .filter_map(|i| {
+            Some(Artist {
+                id: Some(i.id?),
+                name: i.name?,
+            })
+        })
+        .collect()
+}
@@ -1,10 +1,26 @@
+use std::time::Instant;
+
 use color_eyre::eyre::{Result, eyre};
+use serde::Serialize;
 use serde_json::json;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::ConfigArgs;
-use crate::music_api::{DynMusicApi, MusicApiType, Playlist, Song};
-use crate::utils::dedup_songs;
+use crate::checkpoint::Checkpoint;
+use crate::enrich::{build_resolver, enrich_isrcs};
+use crate::mapping::{Mapping, merge_songs};
+use crate::matcher::{DefaultMatcher, MatchPath, MatchThresholds, Matcher};
+use crate::music_api::{
+    Album, Artist, DynMusicApi, MusicApiType, PLAYLIST_DESC, PartialAddError, Playlist, Song,
+};
+use crate::overrides::{OverrideTarget, Overrides};
+use crate::report::{DestinationReport, PlaylistReport, SyncReport};
+use crate::review::review_match;
+use crate::search_cache::SearchCache;
+use crate::utils::{dedup_songs, is_transient_error, media_client};
+
+/// Number of likes added per request, in chronological order
+const LIKES_BATCH_SIZE: usize = 50;
 
 // TODO: Parse playlist owner to ignore platform-specific playlists?
 const SKIPPED_PLAYLISTS: [&str; 10] = [
@@ -22,46 +38,358 @@ const SKIPPED_PLAYLISTS: [&str; 10] = [
     "High Energy Mix",
 ];
 
+#[derive(Serialize, Debug, Default, Clone, Copy)]
+pub struct SyncSummary {
+    pub playlists: usize,
+    pub songs_success: u32,
+    pub songs_attempts: u32,
+    pub likes_success: u32,
+    pub likes_attempts: u32,
+    pub albums_success: u32,
+    pub albums_attempts: u32,
+    pub artists_success: u32,
+    pub artists_attempts: u32,
+    pub isrc_matches: u32,
+    pub fuzzy_matches: u32,
+    pub manual_matches: u32,
+    pub likes_removed: u32,
+    /// Playlists, songs, likes, albums or artists that failed to synchronize
+    pub failures: u32,
+}
+
+impl SyncSummary {
+    pub fn merge(&mut self, other: Self) {
+        self.playlists += other.playlists;
+        self.songs_success += other.songs_success;
+        self.songs_attempts += other.songs_attempts;
+        self.likes_success += other.likes_success;
+        self.likes_attempts += other.likes_attempts;
+        self.albums_success += other.albums_success;
+        self.albums_attempts += other.albums_attempts;
+        self.artists_success += other.artists_success;
+        self.artists_attempts += other.artists_attempts;
+        self.isrc_matches += other.isrc_matches;
+        self.fuzzy_matches += other.fuzzy_matches;
+        self.manual_matches += other.manual_matches;
+        self.likes_removed += other.likes_removed;
+        self.failures += other.failures;
+    }
+
+    pub fn count_match(&mut self, path: MatchPath) {
+        match path {
+            MatchPath::Isrc => self.isrc_matches += 1,
+            MatchPath::Fuzzy => self.fuzzy_matches += 1,
+            MatchPath::Override | MatchPath::Review => self.manual_matches += 1,
+        }
+    }
+}
+
+impl std::fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} playlists, {}/{} songs, {}/{} likes, {}/{} albums, {}/{} artists \
+            ({} ISRC, {} fuzzy, {} manual matches)",
+            self.playlists,
+            self.songs_success,
+            self.songs_attempts,
+            self.likes_success,
+            self.likes_attempts,
+            self.albums_success,
+            self.albums_attempts,
+            self.artists_success,
+            self.artists_attempts,
+            self.isrc_matches,
+            self.fuzzy_matches,
+            self.manual_matches
+        )?;
+        if self.likes_removed > 0 {
+            write!(f, ", {} likes removed", self.likes_removed)?;
+        }
+        if self.failures > 0 {
+            write!(f, ", {} failures", self.failures)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run state shared by the synchronization of all the destinations
+pub struct SyncContext {
+    /// Index of the destination being synchronized in the destinations chain, the same
+    /// platform can be chained several times with different accounts
+    pub destination: usize,
+    pub overrides: Overrides,
+    pub cache: SearchCache,
+    pub checkpoint: Checkpoint,
+    pub report: SyncReport,
+}
+
+impl SyncContext {
+    pub fn from_config(config: &ConfigArgs, source: Option<MusicApiType>) -> Result<Self> {
+        Ok(Self {
+            destination: 0,
+            overrides: Overrides::from_config(config)?,
+            cache: SearchCache::from_config(config)?,
+            checkpoint: Checkpoint::from_config(config)?,
+            report: SyncReport::new(source),
+        })
+    }
+
+    /// End of the run, the state file is kept to resume the failed steps
+    pub fn finish(&mut self, failures: u32) -> Result<()> {
+        if failures > 0 {
+            self.checkpoint.save()?;
+            if self.checkpoint.is_persistent() {
+                info!("run again with --resume to retry the failed steps only");
+            }
+            return Err(PartialSyncError { failures }.into());
+        }
+        self.checkpoint.finish()
+    }
+}
+
+/// Some playlists, songs, likes or albums failed to synchronize, while the rest of the
+/// library was synchronized
+#[derive(Debug)]
+pub struct PartialSyncError {
+    pub failures: u32,
+}
+
+impl std::fmt::Display for PartialSyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} failures during the synchronization, see the summary above",
+            self.failures
+        )
+    }
+}
+
+impl std::error::Error for PartialSyncError {}
+
 pub async fn synchronize(
     src_api: DynMusicApi,
-    dst_api: DynMusicApi,
+    dst_apis: Vec<DynMusicApi>,
     config: ConfigArgs,
 ) -> Result<()> {
-    if !config.diff_country
-        && src_api.api_type() != MusicApiType::YtMusic
-        && dst_api.api_type() != MusicApiType::YtMusic
-        && src_api.country_code() != dst_api.country_code()
-    {
-        return Err(eyre!(
-            "source and destination music platforms are in different countries ({} vs {}). \
-                You can specify --diff-country to allow it, \
-                but this might result in incorrect sync results.",
-            src_api.country_code(),
-            dst_api.country_code()
-        ));
+    for dst_api in &dst_apis {
+        if !config.diff_country
+            && src_api.api_type() != MusicApiType::YtMusic
+            && dst_api.api_type() != MusicApiType::YtMusic
+            && src_api.country_code() != dst_api.country_code()
+        {
+            return Err(eyre!(
+                "source and destination music platforms are in different countries ({} vs {}). \
+                    You can specify --diff-country to allow it, \
+                    but this might result in incorrect sync results.",
+                src_api.country_code(),
+                dst_api.country_code()
+            ));
+        }
     }
 
+    let started = Instant::now();
+    let thresholds = MatchThresholds::from_config(&config);
+    debug!(
+        "matching with profile {:?}: {:?}",
+        config.match_profile, thresholds
+    );
     if config.debug {
         std::fs::create_dir_all("debug")?;
+        std::fs::write(
+            "debug/match_profile.json",
+            serde_json::to_string_pretty(&json!({
+                "profile": config.match_profile,
+                "thresholds": thresholds,
+            }))?,
+        )?;
     }
+    let mapping = Mapping::load_opt(config.mapping.as_deref())?;
+    let mut ctx = SyncContext::from_config(&config, Some(src_api.api_type()))?;
 
+    // the source library is only fetched once, whatever the number of destinations
     info!("retrieving source playlists...");
-    let src_playlists = src_api.get_playlists_full().await?;
+    let mut src_playlists = src_api.get_playlists_full().await?;
+    let matcher = DefaultMatcher::from_config(&config);
+    mapping.apply_merges(&mut src_playlists, &matcher)?;
+    let mut src_likes = vec![];
+    if config.sync_likes || mapping.likes_playlist.is_some() {
+        info!("retrieving source likes...");
+        src_likes = src_api.get_likes().await?;
+    }
+    let mut src_albums = vec![];
+    if config.sync_albums {
+        info!("retrieving source saved albums...");
+        src_albums = src_api.get_saved_albums().await?;
+    }
+    let mut src_artists = vec![];
+    if config.sync_artists {
+        info!("retrieving source followed artists...");
+        src_artists = src_api.get_followed_artists().await?;
+    }
+    if let Some(kind) = config.isrc_resolver {
+        let resolver = build_resolver(kind, &dst_apis, &config)?;
+        let mut songs: Vec<&mut Song> = src_playlists
+            .iter_mut()
+            .flat_map(|p| p.songs.iter_mut())
+            .chain(src_likes.iter_mut())
+            .collect();
+        enrich_isrcs(&mut songs, resolver.as_ref(), &config).await?;
+    }
+    let playlists_likes = mapping.take_likes_playlists(&mut src_playlists, &matcher);
+    mapping.apply_likes_playlist(&src_likes, &mut src_playlists);
+    if !config.sync_likes {
+        src_likes.clear();
+    }
+    merge_songs(&mut src_likes, &playlists_likes, &matcher);
+    let sync_likes = config.sync_likes || !mapping.to_likes.is_empty();
+
+    for (i, dst_api) in dst_apis.iter().enumerate() {
+        info!("synchronizing to {:?}...", dst_api.api_type());
+        ctx.destination = i;
+        let dst_started = Instant::now();
+        let mut summary = SyncSummary::default();
+        let res =
+            synchronize_playlists(src_playlists.clone(), dst_api, &mapping, &mut ctx, &config)
+                .await;
+        match res {
+            Ok(playlists_summary) => summary.merge(playlists_summary),
+            Err(e) => record_failure(&mut ctx.report, &mut summary, dst_api, "playlists", &e),
+        }
 
-    synchronize_playlists(src_playlists, &dst_api, &config).await?;
+        let likes_step = Checkpoint::likes_step(ctx.destination, &dst_api.api_type());
+        if sync_likes && !ctx.checkpoint.was_completed(&likes_step) {
+            let mut res = synchronize_likes(&src_likes, dst_api, &mut ctx, &config).await;
+            if let Err(e) = &res
+                && is_transient_error(e)
+            {
+                warn!(
+                    "transient failure while synchronizing likes, retrying: {}",
+                    e
+                );
+                res = synchronize_likes(&src_likes, dst_api, &mut ctx, &config).await;
+            }
+            match res {
+                Ok(likes_summary) => {
+                    if likes_summary.failures == 0 {
+                        ctx.checkpoint.complete(&likes_step)?;
+                    }
+                    summary.merge(likes_summary);
+                }
+                Err(e) => record_failure(&mut ctx.report, &mut summary, dst_api, "likes", &e),
+            }
+        }
+        // the counterparts of the source likes are unknown until all of them were searched
+        if config.mirror_likes && ctx.checkpoint.is_completed(&likes_step) {
+            match mirror_likes(&src_likes, dst_api, &ctx, &config).await {
+                Ok(mirror_summary) => summary.merge(mirror_summary),
+                Err(e) => {
+                    record_failure(&mut ctx.report, &mut summary, dst_api, "like removals", &e);
+                }
+            }
+        } else if config.mirror_likes {
+            warn!(
+                "likes not fully synchronized to {:?}, not removing any like",
+                dst_api.api_type()
+            );
+        }
+        let albums_step = Checkpoint::albums_step(ctx.destination, &dst_api.api_type());
+        if config.sync_albums && !ctx.checkpoint.was_completed(&albums_step) {
+            let mut res = synchronize_albums(&src_albums, dst_api).await;
+            if let Err(e) = &res
+                && is_transient_error(e)
+            {
+                warn!(
+                    "transient failure while synchronizing albums, retrying: {}",
+                    e
+                );
+                res = synchronize_albums(&src_albums, dst_api).await;
+            }
+            match res {
+                Ok(albums_summary) => {
+                    ctx.checkpoint.complete(&albums_step)?;
+                    summary.merge(albums_summary);
+                }
+                Err(e) => record_failure(&mut ctx.report, &mut summary, dst_api, "albums", &e),
+            }
+        }
+        let artists_step = Checkpoint::artists_step(ctx.destination, &dst_api.api_type());
+        if config.sync_artists && !ctx.checkpoint.was_completed(&artists_step) {
+            let mut res = synchronize_artists(&src_artists, &src_api, dst_api).await;
+            if let Err(e) = &res
+                && is_transient_error(e)
+            {
+                warn!(
+                    "transient failure while synchronizing artists, retrying: {}",
+                    e
+                );
+                res = synchronize_artists(&src_artists, &src_api, dst_api).await;
+            }
+            match res {
+                Ok(artists_summary) => {
+                    ctx.checkpoint.complete(&artists_step)?;
+                    summary.merge(artists_summary);
+                }
+                Err(e) => record_failure(&mut ctx.report, &mut summary, dst_api, "artists", &e),
+            }
+        }
+        ctx.report.destinations.push(DestinationReport {
+            index: ctx.destination,
+            platform: dst_api.api_type(),
+            summary,
+            duration_ms: elapsed_ms(dst_started),
+        });
+    }
 
-    if config.sync_likes {
-        synchronize_likes(&src_api, &dst_api).await?;
+    info!("synchronization summary:");
+    for dst in &ctx.report.destinations {
+        info!("  {:?}: {}", dst.platform, dst.summary);
+    }
+    ctx.report.duration_ms = elapsed_ms(started);
+    if let Some(path) = &config.report {
+        ctx.report.save(path, config.report_format)?;
+        info!("synchronization report saved to {}", path.display());
     }
 
-    Ok(())
+    let failures = ctx
+        .report
+        .destinations
+        .iter()
+        .map(|d| d.summary.failures)
+        .sum();
+    ctx.finish(failures)
+}
+
+fn record_failure(
+    report: &mut SyncReport,
+    summary: &mut SyncSummary,
+    dst_api: &DynMusicApi,
+    scope: &str,
+    err: &color_eyre::eyre::Report,
+) {
+    error!(
+        "failed to synchronize {} to {:?}: {}",
+        scope,
+        dst_api.api_type(),
+        err
+    );
+    report.errors.push(format!(
+        "{:?}: failed to synchronize {}: {}",
+        dst_api.api_type(),
+        scope,
+        err
+    ));
+    summary.failures += 1;
 }
 
 pub async fn synchronize_playlists(
     src_playlists: Vec<Playlist>,
     dst_api: &DynMusicApi,
+    mapping: &Mapping,
+    ctx: &mut SyncContext,
     config: &ConfigArgs,
-) -> Result<()> {
+) -> Result<SyncSummary> {
+    let mut summary = SyncSummary::default();
     let mut all_missing_songs = json!({});
     let mut all_new_songs = json!({});
     let mut no_albums = json!({});
@@ -75,217 +403,539 @@ pub async fn synchronize_playlists(
         dst_likes = dst_api.get_likes().await?;
     }
 
-    for mut src_playlist in src_playlists
+    let mut pending: Vec<Playlist> = src_playlists
         .into_iter()
-        .filter(|p| !SKIPPED_PLAYLISTS.contains(&p.name.as_str()) && !p.songs.is_empty())
-    {
-        if src_playlist.songs.is_empty() {
-            continue;
-        }
+        // the likes playlist is named by the user, even after a platform playlist
+        .filter(|p| {
+            (p.id == Mapping::LIKES_ID || !SKIPPED_PLAYLISTS.contains(&p.name.as_str()))
+                && !p.songs.is_empty()
+        })
+        .collect();
+    let mut retrying = false;
+    while !pending.is_empty() {
+        let mut failed = vec![];
+        for mut src_playlist in pending {
+            let step =
+                Checkpoint::playlist_step(ctx.destination, &dst_api.api_type(), &src_playlist.id);
+            if ctx.checkpoint.was_completed(&step) {
+                info!(
+                    "playlist \"{}\" already synchronized by the resumed run, skipping",
+                    src_playlist.name
+                );
+                continue;
+            }
+            let started = Instant::now();
+            let mut playlist_report = PlaylistReport::new(
+                ctx.destination,
+                dst_api.api_type(),
+                &src_playlist.name,
+                &src_playlist.name,
+            );
+            // failures scoped to a song, the rest of the playlist is synchronized
+            let mut song_failures = 0;
+            let mut transient = false;
+            // only merged into the summary once the playlist is not retried anymore
+            let mut playlist_summary = SyncSummary::default();
 
-        let mut dst_playlist = match dst_playlists
-            .iter()
-            .position(|p| p.name == src_playlist.name)
-        {
-            Some(i) => dst_playlists.remove(i),
-            None => dst_api.create_playlist(&src_playlist.name, false).await?,
-        };
+            let res: Result<()> = async {
+                let dst_name = mapping.dst_name(&src_playlist)?;
+                if dst_name != src_playlist.name {
+                    debug!(
+                        "source playlist \"{}\" mapped to \"{}\"",
+                        src_playlist.name, dst_name
+                    );
+                }
+                playlist_report.destination_name.clone_from(&dst_name);
+                let description = src_playlist.description.as_deref().unwrap_or(PLAYLIST_DESC);
+                let public = src_playlist.public.unwrap_or(false);
+                // the matched and created playlists stay in the lookup, so that the source
+                // playlists mapped to the same name are appended to the same playlist
+                let i = if let Some(i) = dst_playlists.iter().position(|p| p.name == dst_name) {
+                    let dst_playlist = &mut dst_playlists[i];
+                    // only propagate the details exposed by the source platform
+                    let outdated = (src_playlist.description.is_some()
+                        && dst_playlist.description.as_deref() != Some(description))
+                        || (src_playlist.public.is_some() && dst_playlist.public != Some(public));
+                    if outdated {
+                        debug!("updating details of playlist \"{}\"", dst_name);
+                        if let Err(e) = dst_api
+                            .update_playlist_details(dst_playlist, description, public)
+                            .await
+                        {
+                            warn!(
+                                "failed to update details of playlist \"{}\": {}",
+                                dst_name, e
+                            );
+                            playlist_report
+                                .errors
+                                .push(format!("failed to update details: {}", e));
+                        }
+                    }
+                    i
+                } else {
+                    let mut dst_playlist = dst_api
+                        .create_playlist(&dst_name, description, public)
+                        .await?;
+                    playlist_report.created = true;
+                    // the cover is only uploaded once, when the playlist is created
+                    if let Some(cover_url) = &src_playlist.cover_url
+                        && let Err(e) =
+                            upload_cover(dst_api, &mut dst_playlist, cover_url, config).await
+                    {
+                        warn!("failed to upload cover of playlist \"{}\": {}", dst_name, e);
+                        playlist_report
+                            .errors
+                            .push(format!("failed to upload cover: {}", e));
+                    }
+                    dst_playlists.push(dst_playlist);
+                    dst_playlists.len() - 1
+                };
+                let dst_playlist = &mut dst_playlists[i];
 
-        let mut missing_songs = json!([]);
-        let mut new_songs = json!([]);
-        let mut no_albums_songs = json!([]);
-        let mut dst_songs = vec![];
-        let mut success = 0;
-        let mut attempts = 0;
+                let mut missing_songs = json!([]);
+                let mut new_songs = json!([]);
+                let mut no_albums_songs = json!([]);
+                let mut dst_songs = vec![];
+                let mut matches = SyncSummary::default();
+                let mut success = 0;
+                let mut attempts = 0;
 
-        if dedup_songs(&mut src_playlist.songs) {
-            warn!(
-                "duplicates found in source playlist \"{}\", they will be skipped",
-                src_playlist.name
-            );
-        }
+                let src_len = src_playlist.songs.len();
+                if dedup_songs(&mut src_playlist.songs) {
+                    warn!(
+                        "duplicates found in source playlist \"{}\", they will be skipped",
+                        src_playlist.name
+                    );
+                }
+                playlist_report.duplicates = src_len - src_playlist.songs.len();
 
-        info!("synchronizing playlist \"{}\" ...", src_playlist.name);
+                info!("synchronizing playlist \"{}\" ...", src_playlist.name);
 
-        // 1. Search for each song in the destination playlist
-        for src_song in &src_playlist.songs {
-            // already in destination playlist
-            if dst_playlist.songs.contains(src_song) {
-                continue;
-            }
-            // no album metadata == youtube video
-            if src_song.album.is_none() {
-                warn!(
-                    "No album metadata for source song \"{}\", skipping",
-                    src_song
-                );
-                if config.debug {
-                    no_albums_songs
-                        .as_array_mut()
-                        .unwrap()
-                        .push(json!(src_song));
+                // 1. Search for each song in the destination playlist
+                for src_song in &src_playlist.songs {
+                    // already in destination playlist
+                    if contains_match(dst_api, &dst_playlist.songs, src_song) {
+                        playlist_report.skipped += 1;
+                        continue;
+                    }
+                    if let Some(target) = ctx.overrides.get(src_song, &dst_api.api_type()) {
+                        if let Some(dst_song) = target.resolve(src_song, dst_api.api_type()) {
+                            if dst_playlist.songs.iter().any(|s| s.id == dst_song.id) {
+                                debug!("overridden song already in playlist: {}", dst_song);
+                                playlist_report.skipped += 1;
+                                continue;
+                            }
+                            attempts += 1;
+                            success += 1;
+                            dst_songs.push((dst_song, MatchPath::Override));
+                        } else {
+                            debug!("skipping overridden song: {}", src_song);
+                            playlist_report.skipped += 1;
+                        }
+                        continue;
+                    }
+                    // no album metadata == youtube video
+                    if src_song.album.is_none() {
+                        warn!(
+                            "No album metadata for source song \"{}\", skipping",
+                            src_song
+                        );
+                        if config.debug {
+                            no_albums_songs
+                                .as_array_mut()
+                                .unwrap()
+                                .push(json!(src_song));
+                        }
+                        playlist_report.skipped += 1;
+                        continue;
+                    }
+
+                    attempts += 1;
+
+                    let dst_song = match resolve_song(src_song, dst_api, ctx, config).await {
+                        Ok(dst_song) => dst_song,
+                        Err(e) => {
+                            warn!("failed to search song \"{}\": {}", src_song, e);
+                            transient |= is_transient_error(&e);
+                            song_failures += 1;
+                            playlist_report
+                                .errors
+                                .push(format!("failed to search song \"{}\": {}", src_song, e));
+                            continue;
+                        }
+                    };
+                    let Some((dst_song, path)) = dst_song else {
+                        debug!("no match found for song: {}", src_song);
+                        if config.debug {
+                            missing_songs.as_array_mut().unwrap().push(json!(src_song));
+                        }
+                        playlist_report.missing.push(src_song.to_string());
+                        continue;
+                    };
+                    dst_songs.push((dst_song, path));
+                    success += 1;
                 }
-                continue;
-            }
+                ctx.cache.save()?;
 
-            attempts += 1;
+                // 2. Add missing songs to the destination playlist
+                if !dst_songs.is_empty() {
+                    let mut to_sync = Vec::new();
+                    for (dst_song, path) in &dst_songs {
+                        // HACK: takes into account discrepancy for YtMusic with no ISRC
+                        if contains_match(dst_api, &dst_playlist.songs, dst_song) {
+                            debug!(
+                                "discrepancy, song already in destination playlist: {}",
+                                dst_song
+                            );
+                            attempts -= 1;
+                            success -= 1;
+                            playlist_report.skipped += 1;
+                            continue;
+                        }
+                        // Edge case: same song on different album/single that all resolve to the
+                        // same song on the destination platform resulting in duplicates
+                        if contains_match(dst_api, &to_sync, dst_song) {
+                            debug!(
+                                "discrepancy, duplicate song in songs to synchronize: {}",
+                                dst_song
+                            );
+                            attempts -= 1;
+                            success -= 1;
+                            playlist_report.duplicates += 1;
+                            continue;
+                        }
+                        if config.debug {
+                            let mut new_song = json!(dst_song);
+                            new_song["match_path"] = json!(path);
+                            new_songs.as_array_mut().unwrap().push(new_song);
+                        }
+                        matches.count_match(*path);
+                        to_sync.push(dst_song.clone());
+                    }
+                    let res = dst_api.add_songs_to_playlist(dst_playlist, &to_sync).await;
+                    // the songs rejected by the platform don't abort the playlist
+                    if let Err(e) = res {
+                        let partial = e.downcast::<PartialAddError>()?;
+                        for (song, err) in partial.failed {
+                            playlist_report
+                                .errors
+                                .push(format!("failed to add song \"{}\": {}", song, err));
+                            to_sync.retain(|s| s.id != song.id);
+                            success -= 1;
+                            song_failures += 1;
+                        }
+                    }
+                    playlist_report.added = to_sync.len();
 
-            let dst_song = dst_api.search_song(src_song).await?;
-            let Some(dst_song) = dst_song else {
-                debug!("no match found for song: {}", src_song);
-                if config.debug {
-                    missing_songs.as_array_mut().unwrap().push(json!(src_song));
+                    // like all songs that were added
+                    if config.like_all {
+                        let new_likes = to_sync
+                            .iter()
+                            .filter(|s| !contains_match(dst_api, &dst_likes, s))
+                            .cloned()
+                            .collect::<Vec<Song>>();
+                        dst_api.add_likes(&new_likes).await?;
+                    }
                 }
-                continue;
-            };
-            dst_songs.push(dst_song);
-            success += 1;
-        }
 
-        // 2. Add missing songs to the destination playlist
-        if !dst_songs.is_empty() {
-            let mut to_sync = Vec::new();
-            for dst_song in &dst_songs {
-                // HACK: takes into account discrepancy for YtMusic with no ISRC
-                if dst_playlist.songs.contains(dst_song) {
-                    debug!(
-                        "discrepancy, song already in destination playlist: {}",
-                        dst_song
-                    );
-                    attempts -= 1;
-                    success -= 1;
-                    continue;
+                // the failed songs are searched again by the resumed run
+                if song_failures == 0 {
+                    ctx.checkpoint.complete(&step)?;
                 }
-                // Edge case: same song on different album/single that all resolve to the same
-                // song on the destination platform resulting in duplicates
-                if to_sync.contains(dst_song) {
-                    debug!(
-                        "discrepancy, duplicate song in songs to synchronize: {}",
-                        dst_song
+                playlist_report.isrc_matches = matches.isrc_matches;
+                playlist_report.fuzzy_matches = matches.fuzzy_matches;
+                playlist_report.manual_matches = matches.manual_matches;
+
+                playlist_summary.merge(matches);
+                playlist_summary.playlists += 1;
+                playlist_summary.songs_success += success;
+                playlist_summary.songs_attempts += attempts;
+
+                let mut conversion_rate = 1.0;
+                if attempts != 0 {
+                    conversion_rate = f64::from(success) / f64::from(attempts);
+                    info!(
+                        "synchronizing playlist \"{}\" [ok], {}/{} songs ({:.2}%)",
+                        src_playlist.name,
+                        success,
+                        attempts,
+                        conversion_rate * 100.0
+                    );
+                } else {
+                    info!(
+                        "synchronizing playlist \"{}\" [ok], no new songs to add",
+                        src_playlist.name
                     );
-                    attempts -= 1;
-                    success -= 1;
-                    continue;
                 }
+
                 if config.debug {
-                    new_songs.as_array_mut().unwrap().push(json!(dst_song));
+                    stats.as_object_mut().unwrap().insert(
+                        src_playlist.name.clone(),
+                        json!({
+                            "percentage": conversion_rate,
+                            "number": format!("{}/{}", success, attempts),
+                            "match_paths": {
+                                "isrc": matches.isrc_matches,
+                                "fuzzy": matches.fuzzy_matches,
+                                "manual": matches.manual_matches,
+                            },
+                        }),
+                    );
+                    std::fs::write(
+                        "debug/conversion_rate.json",
+                        serde_json::to_string_pretty(&stats)?,
+                    )?;
+
+                    if !new_songs.as_array().unwrap().is_empty() {
+                        all_new_songs
+                            .as_object_mut()
+                            .unwrap()
+                            .insert(src_playlist.name.clone(), new_songs);
+                        std::fs::write(
+                            "debug/new_songs.json",
+                            serde_json::to_string_pretty(&all_new_songs)?,
+                        )?;
+                    }
+
+                    if !missing_songs.as_array().unwrap().is_empty() {
+                        all_missing_songs
+                            .as_object_mut()
+                            .unwrap()
+                            .insert(src_playlist.name.clone(), missing_songs);
+                        std::fs::write(
+                            "debug/missing_songs.json",
+                            serde_json::to_string_pretty(&all_missing_songs)?,
+                        )?;
+                    }
+
+                    if !no_albums_songs.as_array().unwrap().is_empty() {
+                        no_albums
+                            .as_object_mut()
+                            .unwrap()
+                            .insert(src_playlist.name.clone(), no_albums_songs);
+                        std::fs::write(
+                            "debug/song_with_no_albums.json",
+                            serde_json::to_string_pretty(&no_albums)?,
+                        )?;
+                    }
                 }
-                to_sync.push(dst_song.clone());
+                Ok(())
+            }
+            .await;
+
+            let mut failures = song_failures;
+            if let Err(e) = res {
+                warn!(
+                    "failed to synchronize playlist \"{}\": {}",
+                    src_playlist.name, e
+                );
+                transient |= is_transient_error(&e);
+                failures += 1;
+                playlist_report
+                    .errors
+                    .push(format!("failed to synchronize playlist: {}", e));
             }
-            dst_api
-                .add_songs_to_playlist(&mut dst_playlist, &to_sync)
-                .await?;
-
-            // like all songs that were added
-            if config.like_all {
-                let new_likes = to_sync
-                    .iter()
-                    .filter(|s| !dst_likes.contains(s))
-                    .cloned()
-                    .collect::<Vec<Song>>();
-                dst_api.add_likes(&new_likes).await?;
+            playlist_report.duration_ms = elapsed_ms(started);
+            // the songs added and the errors of the first attempt are kept in the report of
+            // the retry, the added songs being already in the playlist found by the retry
+            if retrying
+                && let Some(i) = ctx.report.playlists.iter().position(|p| {
+                    p.destination_index == ctx.destination && p.name == src_playlist.name
+                })
+            {
+                let previous = ctx.report.playlists.remove(i);
+                let previous_added = u32::try_from(previous.added).unwrap_or(u32::MAX);
+                playlist_report.created |= previous.created;
+                playlist_report.added += previous.added;
+                playlist_report.skipped = playlist_report.skipped.saturating_sub(previous.added);
+                playlist_report.isrc_matches += previous.isrc_matches;
+                playlist_report.fuzzy_matches += previous.fuzzy_matches;
+                playlist_report.manual_matches += previous.manual_matches;
+                playlist_summary.songs_success += previous_added;
+                playlist_summary.songs_attempts += previous_added;
+                playlist_summary.isrc_matches += previous.isrc_matches;
+                playlist_summary.fuzzy_matches += previous.fuzzy_matches;
+                playlist_summary.manual_matches += previous.manual_matches;
+                let mut errors: Vec<String> = previous
+                    .errors
+                    .into_iter()
+                    .map(|e| format!("first attempt: {}", e))
+                    .collect();
+                errors.append(&mut playlist_report.errors);
+                playlist_report.errors = errors;
+            }
+            ctx.report.playlists.push(playlist_report);
+
+            if transient && !retrying {
+                failed.push(src_playlist);
+            } else {
+                playlist_summary.failures += failures;
+                summary.merge(playlist_summary);
             }
         }
 
-        let mut conversion_rate = 1.0;
-        if attempts != 0 {
-            conversion_rate = f64::from(success) / f64::from(attempts);
-            info!(
-                "synchronizing playlist \"{}\" [ok], {}/{} songs ({:.2}%)",
-                src_playlist.name,
-                success,
-                attempts,
-                conversion_rate * 100.0
-            );
-        } else {
+        pending = vec![];
+        if !failed.is_empty() && !retrying {
             info!(
-                "synchronizing playlist \"{}\" [ok], no new songs to add",
-                src_playlist.name
+                "retrying {} playlists after transient failures...",
+                failed.len()
             );
+            retrying = true;
+            pending = failed;
+            // playlists created or modified by the failed attempts
+            dst_playlists = dst_api.get_playlists_full().await?;
         }
+    }
 
-        if config.debug {
-            stats.as_object_mut().unwrap().insert(
-                src_playlist.name.clone(),
-                json!({
-                    "percentage": conversion_rate,
-                    "number": format!("{}/{}", success, attempts),
-                }),
-            );
-            std::fs::write(
-                "debug/conversion_rate.json",
-                serde_json::to_string_pretty(&stats)?,
-            )?;
-
-            if !new_songs.as_array().unwrap().is_empty() {
-                all_new_songs
-                    .as_object_mut()
-                    .unwrap()
-                    .insert(src_playlist.name.clone(), new_songs);
-                std::fs::write(
-                    "debug/new_songs.json",
-                    serde_json::to_string_pretty(&all_new_songs)?,
-                )?;
-            }
+    info!("Synchronization complete!");
 
-            if !missing_songs.as_array().unwrap().is_empty() {
-                all_missing_songs
-                    .as_object_mut()
-                    .unwrap()
-                    .insert(src_playlist.name.clone(), missing_songs);
-                std::fs::write(
-                    "debug/missing_songs.json",
-                    serde_json::to_string_pretty(&all_missing_songs)?,
-                )?;
-            }
+    Ok(summary)
+}
 
-            if !no_albums_songs.as_array().unwrap().is_empty() {
-                no_albums
-                    .as_object_mut()
-                    .unwrap()
-                    .insert(src_playlist.name.clone(), no_albums_songs);
-                std::fs::write(
-                    "debug/song_with_no_albums.json",
-                    serde_json::to_string_pretty(&no_albums)?,
-                )?;
-            }
-        }
-    }
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
 
-    info!("Synchronization complete!");
+/// Whether one of the songs matches the song, with the matching profile and thresholds of
+/// the run
+fn contains_match(dst_api: &DynMusicApi, songs: &[Song], song: &Song) -> bool {
+    songs.iter().any(|s| dst_api.matcher().is_match(s, song))
+}
 
+async fn upload_cover(
+    dst_api: &DynMusicApi,
+    dst_playlist: &mut Playlist,
+    cover_url: &str,
+    config: &ConfigArgs,
+) -> Result<()> {
+    let res = media_client(config)?.get(cover_url).send().await?;
+    let status = res.status();
+    if !status.is_success() {
+        return Err(eyre!("Invalid HTTP status: {}", status));
+    }
+    let cover = res.bytes().await?;
+    dst_api.upload_playlist_cover(dst_playlist, &cover).await?;
+    dst_playlist.cover_url = Some(cover_url.to_string());
     Ok(())
 }
 
-pub async fn synchronize_likes(src_api: &DynMusicApi, dst_api: &DynMusicApi) -> Result<()> {
-    info!("retrieving source likes...");
-    let src_likes = src_api.get_likes().await?;
+/// Destination song of the source song, the result of the interrupted run is reused
+/// when resuming
+async fn resolve_song(
+    src_song: &Song,
+    dst_api: &DynMusicApi,
+    ctx: &mut SyncContext,
+    config: &ConfigArgs,
+) -> Result<Option<(Song, MatchPath)>> {
+    let dst_type = dst_api.api_type();
+    if let Some(dst_song) = ctx
+        .checkpoint
+        .resolved(src_song, ctx.destination, &dst_type)
+    {
+        return Ok(dst_song.clone());
+    }
+    let dst_song = search_song_reviewed(src_song, dst_api, ctx, config).await?;
+    // the reviewed songs are saved to the overrides, which take precedence over the
+    // checkpoint, so editing an override never leaves a stale decision behind
+    if ctx.overrides.get(src_song, &dst_type).is_none() {
+        ctx.checkpoint
+            .resolve(src_song, ctx.destination, &dst_type, dst_song.clone())?;
+    }
+    Ok(dst_song)
+}
+
+/// Search the destination song, or reuse the cached result of a previous run, letting
+/// the user review the unconfident matches in interactive mode
+async fn search_song_reviewed(
+    src_song: &Song,
+    dst_api: &DynMusicApi,
+    ctx: &mut SyncContext,
+    config: &ConfigArgs,
+) -> Result<Option<(Song, MatchPath)>> {
+    let dst_song = if let Some(entry) = ctx.cache.get(src_song, dst_api) {
+        entry.song.clone()
+    } else {
+        let dst_song = dst_api.search_song_match(src_song).await?;
+        ctx.cache.insert(src_song, dst_api, dst_song.clone())?;
+        dst_song
+    };
+    if !config.interactive {
+        return Ok(dst_song);
+    }
+    let confident = dst_song.as_ref().is_some_and(|(s, _)| {
+        dst_api.matcher().score(src_song, s).confidence >= config.review_confidence
+    });
+    if confident {
+        return Ok(dst_song);
+    }
+    let path = config
+        .overrides
+        .as_deref()
+        .expect("--interactive requires --overrides");
+    let dst_song = dst_song.map(|(song, _)| song);
+    let reviewed = review_match(src_song, dst_song, dst_api, &mut ctx.overrides, path).await?;
+    Ok(reviewed.map(|song| (song, MatchPath::Review)))
+}
+
+pub async fn synchronize_likes(
+    src_likes: &[Song],
+    dst_api: &DynMusicApi,
+    ctx: &mut SyncContext,
+    config: &ConfigArgs,
+) -> Result<SyncSummary> {
     info!("retrieving destination likes...");
     let dst_likes = dst_api.get_likes().await?;
 
     let mut new_likes = Vec::new();
+    let mut matches = SyncSummary::default();
     let mut success = 0;
     let mut attempts = 0;
 
+    // likes are added oldest-first, so that the destination chronological order matches
+    // the source one. The songs without timestamp are liked first, in the source order.
+    let mut src_likes: Vec<&Song> = src_likes.iter().collect();
+    src_likes.sort_by_key(|s| s.added_at);
+
     info!("searching for all missing likes on destination platform...");
     for src_like in src_likes {
-        if dst_likes.contains(&src_like) {
+        if contains_match(dst_api, &dst_likes, src_like) {
+            continue;
+        }
+        let overridden = ctx.overrides.get(src_like, &dst_api.api_type());
+        if overridden == Some(&OverrideTarget::Skip) {
+            debug!("skipping overridden song: {}", src_like);
             continue;
         }
         attempts += 1;
-        let Some(song) = dst_api.search_song(&src_like).await? else {
+        let song = match overridden {
+            Some(target) => target
+                .resolve(src_like, dst_api.api_type())
+                .map(|song| (song, MatchPath::Override)),
+            None => match resolve_song(src_like, dst_api, ctx, config).await {
+                Ok(song) => song,
+                Err(e) => {
+                    warn!("failed to search song \"{}\": {}", src_like, e);
+                    matches.failures += 1;
+                    continue;
+                }
+            },
+        };
+        let Some((mut song, path)) = song else {
             debug!("no match found for song: {}", src_like);
             continue;
         };
         // HACK: takes into account discrepancy for YtMusic with no ISRC
-        if dst_likes.contains(&song) {
+        if contains_match(dst_api, &dst_likes, &song) {
             attempts -= 1;
             debug!("discrepancy, song already liked: {}", song);
             continue;
         }
         success += 1;
+        matches.count_match(path);
+        song.added_at = src_like.added_at;
         new_likes.push(song);
     }
+    ctx.cache.save()?;
 
     if attempts != 0 {
         let conversion_rate = f64::from(success) / f64::from(attempts);
@@ -295,11 +945,518 @@ pub async fn synchronize_likes(src_api: &DynMusicApi, dst_api: &DynMusicApi) ->
             attempts,
             conversion_rate * 100.0
         );
-        dst_api.add_likes(&new_likes).await?;
+        // songs added in a single request may share the same timestamp, the batches are
+        // added one after the other to keep their relative order
+        for batch in new_likes.chunks(LIKES_BATCH_SIZE) {
+            dst_api.add_likes(batch).await?;
+        }
         info!("[ok] synchronized new likes");
     } else {
         info!("[ok] no new likes to synchronize");
     }
 
-    Ok(())
+    Ok(SyncSummary {
+        likes_success: success,
+        likes_attempts: attempts,
+        ..matches
+    })
+}
+
+/// Remove the destination likes without a counterpart among the source likes
+pub async fn mirror_likes(
+    src_likes: &[Song],
+    dst_api: &DynMusicApi,
+    ctx: &SyncContext,
+    config: &ConfigArgs,
+) -> Result<SyncSummary> {
+    info!("retrieving destination likes to mirror...");
+    let dst_likes = dst_api.get_likes().await?;
+    let dst_type = dst_api.api_type();
+
+    // destination songs matched by the source likes, by search or by override
+    let counterparts: Vec<Song> = src_likes
+        .iter()
+        .filter_map(|src_like| match ctx.overrides.get(src_like, &dst_type) {
+            Some(target) => target.resolve(src_like, dst_type.clone()),
+            None => ctx
+                .checkpoint
+                .resolved(src_like, ctx.destination, &dst_type)
+                .and_then(|res| res.as_ref())
+                .map(|(song, _)| song.clone()),
+        })
+        .collect();
+    let to_remove: Vec<Song> = dst_likes
+        .into_iter()
+        .filter(|dst_like| {
+            !counterparts.iter().any(|c| c.id == dst_like.id)
+                && !contains_match(dst_api, src_likes, dst_like)
+        })
+        .collect();
+
+    if to_remove.is_empty() {
+        info!("[ok] no likes to remove");
+        return Ok(SyncSummary::default());
+    }
+    if config.mirror_dry_run {
+        info!("dry run, {} likes would be removed:", to_remove.len());
+        for song in &to_remove {
+            info!("  {}", song);
+        }
+        return Ok(SyncSummary::default());
+    }
+    if to_remove.len() > config.max_like_removals {
+        return Err(eyre!(
+            "{} likes to remove, above the limit of {}. Check them with --mirror-dry-run \
+                and raise --max-like-removals to remove them",
+            to_remove.len(),
+            config.max_like_removals
+        ));
+    }
+
+    info!("removing {} likes...", to_remove.len());
+    for song in &to_remove {
+        debug!("removing like: {}", song);
+    }
+    dst_api.remove_likes(&to_remove).await?;
+    info!("[ok] removed likes");
+    Ok(SyncSummary {
+        likes_removed: u32::try_from(to_remove.len())?,
+        ..Default::default()
+    })
+}
+
+pub async fn synchronize_albums(
+    src_albums: &[Album],
+    dst_api: &DynMusicApi,
+) -> Result<SyncSummary> {
+    info!("retrieving destination saved albums...");
+    let dst_albums = dst_api.get_saved_albums().await?;
+
+    let mut new_albums: Vec<Album> = Vec::new();
+    let mut success = 0;
+    let mut attempts = 0;
+
+    info!("searching for all missing saved albums on destination platform...");
+    for src_album in src_albums {
+        if dst_albums.iter().any(|a| a.compare(src_album)) {
+            continue;
+        }
+        attempts += 1;
+        let Some(album) = dst_api.search_album(src_album).await? else {
+            debug!("no match found for album: {}", src_album);
+            continue;
+        };
+        if dst_albums
+            .iter()
+            .chain(&new_albums)
+            .any(|a| a.compare(&album))
+        {
+            attempts -= 1;
+            debug!("discrepancy, album already saved: {}", album);
+            continue;
+        }
+        success += 1;
+        new_albums.push(album);
+    }
+
+    if attempts != 0 {
+        let conversion_rate = f64::from(success) / f64::from(attempts);
+        info!(
+            "synchronizing {}/{} ({:.2}%) new saved albums",
+            success,
+            attempts,
+            conversion_rate * 100.0
+        );
+        dst_api.add_saved_albums(&new_albums).await?;
+        info!("[ok] synchronized new saved albums");
+    } else {
+        info!("[ok] no new saved albums to synchronize");
+    }
+
+    Ok(SyncSummary {
+        albums_success: success,
+        albums_attempts: attempts,
+        ..Default::default()
+    })
+}
+
+/// Follow the source artists on the destination platform, along with the source top songs
+/// used to match them
+pub async fn synchronize_artists(
+    src_artists: &[Artist],
+    src_api: &DynMusicApi,
+    dst_api: &DynMusicApi,
+) -> Result<SyncSummary> {
+    info!("retrieving destination followed artists...");
+    let dst_artists = dst_api.get_followed_artists().await?;
+
+    let mut new_artists: Vec<Artist> = Vec::new();
+    let mut success = 0;
+    let mut attempts = 0;
+
+    info!("searching for all missing followed artists on destination platform...");
+    for src_artist in src_artists {
+        if dst_artists.iter().any(|a| a.compare(src_artist)) {
+            continue;
+        }
+        attempts += 1;
+        let Some(artist) = dst_api.search_artist(src_artist, src_api).await? else {
+            debug!("no match found for artist: {}", src_artist.name);
+            continue;
+        };
+        if dst_artists
+            .iter()
+            .chain(&new_artists)
+            .any(|a| a.id == artist.id)
+        {
+            attempts -= 1;
+            debug!("discrepancy, artist already followed: {}", artist.name);
+            continue;
+        }
+        success += 1;
+        new_artists.push(artist);
+    }
+
+    if attempts != 0 {
+        let conversion_rate = f64::from(success) / f64::from(attempts);
+        info!(
+            "synchronizing {}/{} ({:.2}%) new followed artists",
+            success,
+            attempts,
+            conversion_rate * 100.0
+        );
+        dst_api.follow_artists(&new_artists).await?;
+        info!("[ok] synchronized new followed artists");
+    } else {
+        info!("[ok] no new followed artists to synchronize");
+    }
+
+    Ok(SyncSummary {
+        artists_success: success,
+        artists_attempts: attempts,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use clap::Parser;
+
+    use super::*;
+    use crate::music_api::MusicApi;
+
+    /// Destination platform without playlists, on which every source song is found
+    struct MockApi {
+        config: ConfigArgs,
+        likes: std::sync::Mutex<Vec<Song>>,
+    }
+
+    fn mock_api(config: &ConfigArgs) -> DynMusicApi {
+        mock_api_with_likes(config, vec![])
+    }
+
+    fn mock_api_with_likes(config: &ConfigArgs, likes: Vec<Song>) -> DynMusicApi {
+        Box::new(MockApi {
+            config: config.clone(),
+            likes: std::sync::Mutex::new(likes),
+        })
+    }
+
+    fn context() -> SyncContext {
+        SyncContext {
+            destination: 0,
+            overrides: Overrides::default(),
+            cache: SearchCache::disabled(),
+            checkpoint: Checkpoint::disabled(),
+            report: SyncReport::new(Some(MusicApiType::Spotify)),
+        }
+    }
+
+    #[async_trait]
+    impl MusicApi for MockApi {
+        fn api_type(&self) -> MusicApiType {
+            MusicApiType::Tidal
+        }
+
+        fn country_code(&self) -> &'static str {
+            "US"
+        }
+
+        fn config(&self) -> &ConfigArgs {
+            &self.config
+        }
+
+        async fn create_playlist(&self, name: &str, _: &str, public: bool) -> Result<Playlist> {
+            Ok(Playlist {
+                id: name.to_string(),
+                name: name.to_string(),
+                description: None,
+                public: Some(public),
+                cover_url: None,
+                songs: vec![],
+            })
+        }
+
+        async fn get_playlists_info(&self) -> Result<Vec<Playlist>> {
+            Ok(vec![])
+        }
+
+        async fn get_playlist_songs(&self, _: &str) -> Result<Vec<Song>> {
+            Ok(vec![])
+        }
+
+        async fn add_songs_to_playlist(
+            &self,
+            playlist: &mut Playlist,
+            songs: &[Song],
+        ) -> Result<()> {
+            playlist.songs.extend_from_slice(songs);
+            Ok(())
+        }
+
+        async fn remove_songs_from_playlist(&self, _: &mut Playlist, _: &[Song]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn delete_playlist(&self, _: Playlist) -> Result<()> {
+            Ok(())
+        }
+
+        async fn update_playlist_details(&self, _: &mut Playlist, _: &str, _: bool) -> Result<()> {
+            Ok(())
+        }
+
+        async fn upload_playlist_cover(&self, _: &mut Playlist, _: &[u8]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn search_song_match(&self, song: &Song) -> Result<Option<(Song, MatchPath)>> {
+            let dst_song = Song {
+                source: MusicApiType::Tidal,
+                id: format!("tidal_{}", song.id),
+                ..song.clone()
+            };
+            Ok(Some((dst_song, MatchPath::Isrc)))
+        }
+
+        async fn search_isrc(&self, _: &Song, _: &str) -> Result<Option<Song>> {
+            Ok(None)
+        }
+
+        async fn search_query(&self, _: &str, _: usize) -> Result<Vec<Song>> {
+            Ok(vec![])
+        }
+
+        async fn add_likes(&self, _: &[Song]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn remove_likes(&self, songs: &[Song]) -> Result<()> {
+            let mut likes = self.likes.lock().unwrap();
+            likes.retain(|l| !songs.iter().any(|s| s.id == l.id));
+            Ok(())
+        }
+
+        async fn get_likes(&self) -> Result<Vec<Song>> {
+            Ok(self.likes.lock().unwrap().clone())
+        }
+
+        async fn get_saved_albums(&self) -> Result<Vec<Album>> {
+            Ok(vec![])
+        }
+
+        async fn search_album(&self, _: &Album) -> Result<Option<Album>> {
+            Ok(None)
+        }
+
+        async fn add_saved_albums(&self, _: &[Album]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_followed_artists(&self) -> Result<Vec<Artist>> {
+            Ok(vec![])
+        }
+
+        async fn search_artist_query(&self, _: &str, _: usize) -> Result<Vec<Artist>> {
+            Ok(vec![])
+        }
+
+        async fn get_artist_top_songs(&self, _: &Artist) -> Result<Vec<Song>> {
+            Ok(vec![])
+        }
+
+        async fn follow_artists(&self, _: &[Artist]) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_likes_playlist_not_skipped() {
+        let config = ConfigArgs::parse_from(["sync_dis_boi"]);
+        let dst_api = mock_api(&config);
+        let mut ctx = context();
+        let mapping: Mapping =
+            serde_json::from_str(r#"{ "likes_playlist": "Liked Songs" }"#).unwrap();
+        let song = Song::test(MusicApiType::Spotify, "a", "Get Lucky").with_album("RAM");
+        let platform_likes = Playlist {
+            id: "1".to_string(),
+            name: "Liked Songs".to_string(),
+            description: None,
+            public: None,
+            cover_url: None,
+            songs: vec![song.clone()],
+        };
+        let mut src_playlists = vec![platform_likes];
+        mapping.apply_likes_playlist(std::slice::from_ref(&song), &mut src_playlists);
+
+        let summary = synchronize_playlists(src_playlists, &dst_api, &mapping, &mut ctx, &config)
+            .await
+            .unwrap();
+        assert_eq!(summary.playlists, 1);
+        assert_eq!(summary.songs_success, 1);
+        let report = &ctx.report.playlists[0];
+        assert_eq!(report.destination_name, "Liked Songs");
+        assert_eq!(report.added, 1);
+    }
+
+    #[tokio::test]
+    async fn test_renamed_playlists_share_destination() {
+        let config = ConfigArgs::parse_from(["sync_dis_boi"]);
+        let dst_api = mock_api(&config);
+        let mut ctx = context();
+        let mapping: Mapping =
+            serde_json::from_str(r#"{ "rename": { "Chill": "Mix", "Party": "Mix" } }"#).unwrap();
+        let playlist = |id: &str, name: &str, song: Song| Playlist {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: None,
+            public: None,
+            cover_url: None,
+            songs: vec![song],
+        };
+        let src_playlists = vec![
+            playlist(
+                "1",
+                "Chill",
+                Song::test(MusicApiType::Spotify, "a", "Get Lucky").with_album("RAM"),
+            ),
+            playlist(
+                "2",
+                "Party",
+                Song::test(MusicApiType::Spotify, "b", "One More Time").with_album("Discovery"),
+            ),
+        ];
+
+        let summary = synchronize_playlists(src_playlists, &dst_api, &mapping, &mut ctx, &config)
+            .await
+            .unwrap();
+        assert_eq!(summary.songs_success, 2);
+        let created: Vec<bool> = ctx.report.playlists.iter().map(|p| p.created).collect();
+        assert_eq!(created, [true, false]);
+    }
+
+    /// Source like, its counterpart found by a previous search under another name, and a
+    /// destination like without counterpart
+    fn mirror_setup(args: &[&str]) -> (ConfigArgs, DynMusicApi, SyncContext, Vec<Song>) {
+        let config = ConfigArgs::parse_from(
+            ["sync_dis_boi", "--sync-likes", "--mirror-likes"]
+                .iter()
+                .chain(args),
+        );
+        let src_like = Song::test(MusicApiType::Spotify, "a", "Get Lucky").with_album("RAM");
+        let counterpart = Song::test(MusicApiType::Tidal, "tidal_a", "ゲット・ラッキー");
+        let other = Song::test(MusicApiType::Tidal, "tidal_b", "One More Time");
+        let dst_api = mock_api_with_likes(&config, vec![counterpart.clone(), other]);
+        let mut ctx = context();
+        ctx.checkpoint
+            .resolve(
+                &src_like,
+                0,
+                &MusicApiType::Tidal,
+                Some((counterpart, MatchPath::Isrc)),
+            )
+            .unwrap();
+        (config, dst_api, ctx, vec![src_like])
+    }
+
+    async fn like_ids(dst_api: &DynMusicApi) -> Vec<String> {
+        let likes = dst_api.get_likes().await.unwrap();
+        likes.into_iter().map(|s| s.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_mirror_likes_counterparts() {
+        let (config, dst_api, ctx, src_likes) = mirror_setup(&[]);
+        let summary = mirror_likes(&src_likes, &dst_api, &ctx, &config)
+            .await
+            .unwrap();
+        assert_eq!(summary.likes_removed, 1);
+        assert_eq!(like_ids(&dst_api).await, ["tidal_a"]);
+    }
+
+    #[tokio::test]
+    async fn test_mirror_likes_dry_run() {
+        let (config, dst_api, ctx, src_likes) = mirror_setup(&["--mirror-dry-run"]);
+        let summary = mirror_likes(&src_likes, &dst_api, &ctx, &config)
+            .await
+            .unwrap();
+        assert_eq!(summary.likes_removed, 0);
+        assert_eq!(like_ids(&dst_api).await, ["tidal_a", "tidal_b"]);
+    }
+
+    #[tokio::test]
+    async fn test_mirror_likes_removals_cap() {
+        let (config, dst_api, ctx, src_likes) = mirror_setup(&["--max-like-removals", "0"]);
+        assert!(
+            mirror_likes(&src_likes, &dst_api, &ctx, &config)
+                .await
+                .is_err()
+        );
+        assert_eq!(like_ids(&dst_api).await, ["tidal_a", "tidal_b"]);
+    }
+
+    #[tokio::test]
+    async fn test_override_precedes_resumed_search() {
+        let config = ConfigArgs::parse_from(["sync_dis_boi"]);
+        let dst_api = mock_api(&config);
+        let mut ctx = context();
+        let song = Song::test(MusicApiType::Spotify, "a", "Get Lucky").with_album("RAM");
+        let resumed = Song::test(MusicApiType::Tidal, "tidal_a", "Get Lucky");
+        ctx.checkpoint
+            .resolve(
+                &song,
+                0,
+                &MusicApiType::Tidal,
+                Some((resumed, MatchPath::Fuzzy)),
+            )
+            .unwrap();
+        // override added after the interrupted run
+        ctx.overrides.add(
+            &song,
+            MusicApiType::Tidal,
+            OverrideTarget::Song("tidal_b".to_string()),
+        );
+        let playlist = Playlist {
+            id: "1".to_string(),
+            name: "Chill".to_string(),
+            description: None,
+            public: None,
+            cover_url: None,
+            songs: vec![song],
+        };
+
+        synchronize_playlists(
+            vec![playlist],
+            &dst_api,
+            &Mapping::default(),
+            &mut ctx,
+            &config,
+        )
+        .await
+        .unwrap();
+        let report = &ctx.report.playlists[0];
+        assert_eq!(report.manual_matches, 1);
+        assert_eq!(report.fuzzy_matches, 0);
+    }
 }
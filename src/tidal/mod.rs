@@ -5,22 +5,31 @@ use std::io::Read;
 use std::path::PathBuf;
 
 use async_trait::async_trait;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
 use color_eyre::Result;
 use color_eyre::eyre::eyre;
 use model::{TidalMediaResponse, TidalMediaResponseSingle, TidalOAuthDeviceRes};
-use reqwest::header::HeaderMap;
+use reqwest::header::{HeaderMap, HeaderValue};
 use serde::de::{DeserializeOwned, IgnoredAny};
 use serde_json::json;
-use tracing::info;
+use tracing::{debug, info, warn};
 
-use self::model::{TidalPageResponse, TidalPlaylistResponse, TidalSongItemResponse};
+use self::model::{
+    TidalArtistItemResponse, TidalArtistSearchResponse, TidalPageResponse, TidalPlaylistResponse,
+    TidalSongItemResponse, TidalSongResponse,
+};
 use crate::ConfigArgs;
+use crate::matcher::{MatchPath, Matcher};
 use crate::music_api::{
-    MusicApi, MusicApiType, OAuthRefreshToken, OAuthReqToken, OAuthToken, PLAYLIST_DESC, Playlist,
-    Playlists, Song, Songs,
+    Album, Albums, Artist, Artists, MusicApi, MusicApiType, OAuthRefreshToken, OAuthReqToken,
+    OAuthToken, PartialAddError, Playlist, Playlists, Song, Songs,
+};
+use crate::tidal::model::{
+    TidalAlbumItemResponse, TidalAlbumSearchResponse, TidalArtworkResponse,
+    TidalPlaylistCreateResponse, TidalSearchResponse,
 };
-use crate::tidal::model::{TidalPlaylistCreateResponse, TidalSearchResponse};
-use crate::utils::debug_response_json;
+use crate::utils::{client_builder, debug_response_json, error_status, media_client};
 
 pub struct TidalApi {
     client: reqwest::Client,
@@ -34,6 +43,7 @@ enum HttpMethod<'a> {
     Get(&'a serde_json::Value),
     Post(&'a serde_json::Value),
     Put(&'a serde_json::Value),
+    Delete(&'a serde_json::Value),
 }
 
 impl TidalApi {
@@ -44,6 +54,10 @@ impl TidalApi {
     const TOKEN_URL: &'static str = "https://auth.tidal.com/v1/oauth2/token";
     const SCOPE: &'static str = "r_usr w_usr w_sub";
     const RES_DEBUG_FILENAME: &'static str = MusicApiType::Tidal.short_name();
+    // NOTE: large additions are rejected, like for the favorites
+    const PLAYLIST_CHUNK_SIZE: usize = 100;
+    // statuses of the additions rejecting unknown (400, 404) or duplicate (409) tracks
+    const REJECTED_STATUSES: [u16; 3] = [400, 404, 409];
 
     pub async fn new(
         client_id: &str,
@@ -70,15 +84,7 @@ impl TidalApi {
         );
         headers.insert("Content-Type", "application/vnd.tidal.v1+json".parse()?);
 
-        let mut client = reqwest::Client::builder()
-            .cookie_store(true)
-            .default_headers(headers);
-        if let Some(proxy) = &config.proxy {
-            client = client
-                .proxy(reqwest::Proxy::all(proxy)?)
-                .danger_accept_invalid_certs(true);
-        }
-        let client = client.build()?;
+        let client = client_builder(&config)?.default_headers(headers).build()?;
 
         let url = format!("{}/users/me", Self::API_V2_URL);
         let res = client.get(&url).send().await?;
@@ -204,6 +210,94 @@ impl TidalApi {
         Ok(res)
     }
 
+    async fn get_playlist_etag(&self, id: &str) -> Result<HeaderValue> {
+        let url = format!("{}/v1/playlists/{}", Self::API_URL, id);
+        let params = json!({
+            "countryCode": self.country_code,
+        });
+        let res = self.client.get(&url).query(&params).send().await?;
+        let status = res.status();
+        let etag = res.headers().get("ETag").cloned();
+        let _: IgnoredAny =
+            debug_response_json(&self.config, res, Self::RES_DEBUG_FILENAME).await?;
+        let etag = etag.ok_or(eyre!("No ETag in Tidal Response"))?;
+        if !status.is_success() {
+            return Err(eyre!("Invalid HTTP status: {}", status));
+        }
+        Ok(etag)
+    }
+
+    async fn add_tracks(&self, playlist_id: &str, songs: &[Song]) -> Result<()> {
+        // 1. query playlist ETag, it changes after every addition
+        let etag = self.get_playlist_etag(playlist_id).await?;
+
+        // 2. add songs to playlist
+        let url = format!("{}/v1/playlists/{}/items", Self::API_URL, playlist_id);
+        let params = json!({
+            "trackIds": songs.iter().map(|s| s.id.as_str()).collect::<Vec<_>>().join(","),
+            "onDuplicate": "FAIL",
+            "onArtifactNotFound": "FAIL",
+        });
+        let res = self
+            .client
+            .post(url)
+            .header("If-None-Match", etag)
+            .form(&params)
+            .send()
+            .await?;
+        let status = res.status();
+        let () = debug_response_json(&self.config, res, Self::RES_DEBUG_FILENAME).await?;
+        if !status.is_success() {
+            return Err(eyre!("Invalid HTTP status: {}", status));
+        }
+        Ok(())
+    }
+
+    /// Add the songs in chunks, splitting a chunk in halves on failure to isolate the
+    /// rejected songs, which are returned along with their error. Only the rejections of
+    /// unknown or duplicate tracks are bisected, the other failures (e.g. authentication,
+    /// outdated playlist tag or rate limiting) abort the whole addition
+    async fn add_tracks_bisect<'a, F, Fut>(
+        songs: &'a [Song],
+        add_tracks: F,
+    ) -> Result<Vec<(Song, String)>>
+    where
+        F: Fn(&'a [Song]) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let mut failed = vec![];
+        for chunk in songs.chunks(Self::PLAYLIST_CHUNK_SIZE) {
+            // the left half is added first, to keep the songs order
+            let mut pending = vec![chunk];
+            while let Some(songs) = pending.pop() {
+                let err = match add_tracks(songs).await {
+                    Ok(()) => continue,
+                    Err(e)
+                        if error_status(&e)
+                            .is_some_and(|s| Self::REJECTED_STATUSES.contains(&s)) =>
+                    {
+                        e
+                    }
+                    Err(e) => return Err(e),
+                };
+                if let [song] = songs {
+                    warn!("failed to add song \"{}\" to playlist: {}", song, err);
+                    failed.push((song.clone(), err.to_string()));
+                    continue;
+                }
+                debug!(
+                    "failed to add {} songs to playlist, bisecting: {}",
+                    songs.len(),
+                    err
+                );
+                let (left, right) = songs.split_at(songs.len() / 2);
+                pending.push(right);
+                pending.push(left);
+            }
+        }
+        Ok(failed)
+    }
+
     async fn make_request_json<T>(
         &self,
         url: &str,
@@ -217,6 +311,7 @@ impl TidalApi {
             HttpMethod::Get(p) => self.client.get(url).query(p),
             HttpMethod::Post(b) => self.client.post(url).form(b),
             HttpMethod::Put(b) => self.client.put(url).form(b),
+            HttpMethod::Delete(p) => self.client.delete(url).query(p),
         };
         if let Some((limit, offset)) = lim_off {
             request = request.query(&[("limit", limit), ("offset", offset)]);
@@ -238,18 +333,27 @@ impl MusicApi for TidalApi {
         MusicApiType::Tidal
     }
 
+    fn config(&self) -> &ConfigArgs {
+        &self.config
+    }
+
     fn country_code(&self) -> &str {
         &self.country_code
     }
 
-    async fn create_playlist(&self, name: &str, public: bool) -> Result<Playlist> {
+    async fn create_playlist(
+        &self,
+        name: &str,
+        description: &str,
+        public: bool,
+    ) -> Result<Playlist> {
         let url = format!(
             "{}/v2/my-collection/playlists/folders/create-playlist",
             Self::API_URL
         );
         let params = json!({
             "name": name,
-            "description": PLAYLIST_DESC,
+            "description": description,
             "public": public,
             "folderId": "root"
         });
@@ -260,6 +364,9 @@ impl MusicApi for TidalApi {
         Ok(Playlist {
             id: res.data.uuid,
             name: name.to_string(),
+            description: Some(description.to_string()),
+            public: Some(public),
+            cover_url: None,
             songs: vec![],
         })
     }
@@ -290,131 +397,336 @@ impl MusicApi for TidalApi {
     }
 
     async fn add_songs_to_playlist(&self, playlist: &mut Playlist, songs: &[Song]) -> Result<()> {
-        if songs.is_empty() {
-            return Ok(());
+        let failed =
+            Self::add_tracks_bisect(songs, |chunk| self.add_tracks(&playlist.id, chunk)).await?;
+        playlist.songs.extend(
+            songs
+                .iter()
+                .filter(|s| !failed.iter().any(|(f, _)| f.id == s.id))
+                .cloned(),
+        );
+        if !failed.is_empty() {
+            return Err(PartialAddError { failed }.into());
         }
+        Ok(())
+    }
+
+    async fn remove_songs_from_playlist(
+        &self,
+        _playlist: &mut Playlist,
+        _songs_ids: &[Song],
+    ) -> Result<()> {
+        todo!()
+    }
+
+    async fn delete_playlist(&self, playlist: Playlist) -> Result<()> {
+        let url = format!(
+            "{}/v2/my-collection/playlists/folders/remove",
+            Self::API_URL
+        );
+        let params = json!({
+            "trns": format!("trn:playlist:{}", playlist.id),
+        });
+        let _: IgnoredAny = self
+            .make_request_json(&url, &HttpMethod::Put(&params), None)
+            .await?;
+        Ok(())
+    }
 
-        // 1. query playlist ETag
+    async fn update_playlist_details(
+        &self,
+        playlist: &mut Playlist,
+        description: &str,
+        public: bool,
+    ) -> Result<()> {
+        // 1. update the description, the playlist ETag is required
+        let etag = self.get_playlist_etag(&playlist.id).await?;
         let url = format!("{}/v1/playlists/{}", Self::API_URL, playlist.id);
         let params = json!({
-            "countryCode": self.country_code,
+            "title": playlist.name,
+            "description": description,
         });
-        let res = self.client.get(&url).query(&params).send().await?;
+        let res = self
+            .client
+            .post(url)
+            .header("If-None-Match", etag)
+            .form(&params)
+            .send()
+            .await?;
         let status = res.status();
-        let etag = res.headers().get("ETag").cloned();
-        let _: IgnoredAny =
-            debug_response_json(&self.config, res, Self::RES_DEBUG_FILENAME).await?;
-        let etag = etag.ok_or(eyre!("No ETag in Tidal Response"))?;
         if !status.is_success() {
             return Err(eyre!("Invalid HTTP status: {}", status));
         }
 
-        // 2. add songs to playlist
-        let url = format!("{}/v1/playlists/{}/items", Self::API_URL, playlist.id);
-        let params = json!({
-            "trackIds": songs.iter().map(|s| s.id.as_str()).collect::<Vec<_>>().join(","),
-            "onDuplicate": "FAIL",
-            "onArtifactNotFound": "FAIL",
+        // 2. update the visibility
+        let visibility = if public { "set-public" } else { "set-private" };
+        let url = format!(
+            "{}/v2/playlists/{}/{}",
+            Self::API_URL,
+            playlist.id,
+            visibility
+        );
+        let res = self.client.put(url).send().await?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(eyre!("Invalid HTTP status: {}", status));
+        }
+
+        playlist.description = Some(description.to_string());
+        playlist.public = Some(public);
+        Ok(())
+    }
+
+    async fn upload_playlist_cover(&self, playlist: &mut Playlist, jpeg: &[u8]) -> Result<()> {
+        const JSON_API: &str = "application/vnd.api+json";
+
+        // 1. create the artwork, returns the link to upload the image to
+        let url = format!("{}/artworks", Self::API_V2_URL);
+        let body = json!({
+            "data": {
+                "type": "artworks",
+                "attributes": {
+                    "mediaType": "IMAGE",
+                    "sourceFile": {
+                        "md5": BASE64_STANDARD.encode(md5::compute(jpeg).0),
+                        "size": jpeg.len(),
+                    },
+                },
+            },
         });
         let res = self
             .client
             .post(url)
-            .header("If-None-Match", etag)
-            .form(&params)
+            .query(&[("countryCode", &self.country_code)])
+            .header("Content-Type", JSON_API)
+            .body(body.to_string())
+            .send()
+            .await?;
+        let status = res.status();
+        let artwork: TidalArtworkResponse =
+            debug_response_json(&self.config, res, Self::RES_DEBUG_FILENAME).await?;
+        if !status.is_success() {
+            return Err(eyre!("Invalid HTTP status: {}", status));
+        }
+        let upload_url = artwork
+            .data
+            .attributes
+            .source_file
+            .ok_or(eyre!("No upload link in Tidal artwork response"))?
+            .upload_link
+            .href;
+
+        // 2. upload the image, the upload link is pre-signed
+        let res = media_client(&self.config)?
+            .put(upload_url)
+            .header("Content-Type", "image/jpeg")
+            .body(jpeg.to_vec())
             .send()
             .await?;
         let status = res.status();
-        let () = debug_response_json(&self.config, res, Self::RES_DEBUG_FILENAME).await?;
         if !status.is_success() {
             return Err(eyre!("Invalid HTTP status: {}", status));
         }
 
+        // 3. set the artwork as the playlist cover
+        let url = format!(
+            "{}/playlists/{}/relationships/coverArt",
+            Self::API_V2_URL,
+            playlist.id
+        );
+        let body = json!({
+            "data": [{ "id": artwork.data.id, "type": "artworks" }],
+        });
+        let res = self
+            .client
+            .patch(url)
+            .header("Content-Type", JSON_API)
+            .body(body.to_string())
+            .send()
+            .await?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(eyre!("Invalid HTTP status: {}", status));
+        }
         Ok(())
     }
 
-    async fn remove_songs_from_playlist(
-        &self,
-        _playlist: &mut Playlist,
-        _songs_ids: &[Song],
-    ) -> Result<()> {
-        todo!()
+    async fn search_song_match(&self, song: &Song) -> Result<Option<(Song, MatchPath)>> {
+        if let Some(isrc) = &song.isrc {
+            if let Some(res_song) = self.search_isrc(song, isrc).await? {
+                return Ok(Some((res_song, MatchPath::Isrc)));
+            }
+            debug!("no ISRC match, falling back to fuzzy search: {}", song);
+        }
+        self.search_fuzzy(song, song.build_queries()).await
     }
 
-    async fn delete_playlist(&self, playlist: Playlist) -> Result<()> {
+    async fn search_isrc(&self, song: &Song, isrc: &str) -> Result<Option<Song>> {
+        let url = format!("{}/tracks", Self::API_V2_URL);
+        let params = json!({
+            "countryCode": self.country_code,
+            "include": "albums,artists",
+            "filter[isrc]": isrc.to_uppercase(),
+        });
+        let res: TidalMediaResponse = self
+            .make_request_json(&url, &HttpMethod::Get(&params), Some((1, 0)))
+            .await?;
+        if res.data.is_empty() {
+            return Ok(None);
+        }
+        let res_songs: Songs = res.try_into()?;
+        Ok(self
+            .matcher()
+            .best_match(song, res_songs.0)
+            .map(|(res_song, _)| res_song))
+    }
+
+    async fn search_query(&self, query: &str, limit: usize) -> Result<Vec<Song>> {
+        let url = format!("{}/v1/search", Self::API_URL);
+        let params = json!({
+            "countryCode": self.country_code,
+            "query": query,
+            "type": "TRACKS",
+        });
+        let res: TidalSearchResponse = self
+            .make_request_json(&url, &HttpMethod::Get(&params), Some((limit, 0)))
+            .await?;
+        let res_songs: Songs = res.try_into()?;
+        Ok(res_songs.0.into_iter().take(limit).collect())
+    }
+
+    async fn add_likes(&self, songs: &[Song]) -> Result<()> {
+        if songs.is_empty() {
+            return Ok(());
+        }
+
         let url = format!(
-            "{}/v2/my-collection/playlists/folders/remove",
-            Self::API_URL
+            "{}/v1/users/{}/favorites/tracks",
+            Self::API_URL,
+            self.user_id
         );
+        let tracks = songs.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+
+        // NOTE: we get error 500 if we like too much songs at once
+        for tracks_chunk in tracks.chunks(100) {
+            let params = json!({
+                "countryCode": self.country_code,
+                "trackIds": tracks_chunk.join(","),
+                "onArtifactNotFound": "FAIL",
+            });
+            let () = self
+                .make_request_json(&url, &HttpMethod::Post(&params), None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn remove_likes(&self, songs: &[Song]) -> Result<()> {
         let params = json!({
-            "trns": format!("trn:playlist:{}", playlist.id),
+            "countryCode": self.country_code,
         });
-        let _: IgnoredAny = self
-            .make_request_json(&url, &HttpMethod::Put(&params), None)
-            .await?;
+        // NOTE: the favorites are removed one at a time
+        for song in songs {
+            let url = format!(
+                "{}/v1/users/{}/favorites/tracks/{}",
+                Self::API_URL,
+                self.user_id,
+                song.id
+            );
+            let () = self
+                .make_request_json(&url, &HttpMethod::Delete(&params), None)
+                .await?;
+        }
         Ok(())
     }
 
-    async fn search_song(&self, song: &Song) -> Result<Option<Song>> {
-        if let Some(isrc) = &song.isrc {
-            let url = format!("{}/tracks", Self::API_V2_URL);
+    async fn get_likes(&self) -> Result<Vec<Song>> {
+        let url = format!(
+            "{}/v1/users/{}/favorites/tracks",
+            Self::API_URL,
+            self.user_id
+        );
+        let params = json!({
+            "countryCode": self.country_code,
+        });
+        let res: TidalPageResponse<TidalSongItemResponse> = self
+            .paginated_request(&url, &HttpMethod::Get(&params), 1000)
+            .await?;
+        let songs: Songs = res.try_into()?;
+        Ok(songs.0)
+    }
+
+    async fn get_saved_albums(&self) -> Result<Vec<Album>> {
+        let url = format!(
+            "{}/v1/users/{}/favorites/albums",
+            Self::API_URL,
+            self.user_id
+        );
+        let params = json!({
+            "countryCode": self.country_code,
+        });
+        let res: TidalPageResponse<TidalAlbumItemResponse> = self
+            .paginated_request(&url, &HttpMethod::Get(&params), 100)
+            .await?;
+        let albums: Albums = res.try_into()?;
+        Ok(albums.0)
+    }
+
+    async fn search_album(&self, album: &Album) -> Result<Option<Album>> {
+        if let Some(upc) = &album.upc {
+            let url = format!("{}/albums", Self::API_V2_URL);
             let params = json!({
                 "countryCode": self.country_code,
-                "include": "albums,artists",
-                "filter[isrc]": isrc.to_uppercase(),
+                "include": "artists",
+                "filter[barcodeId]": upc,
             });
             let res: TidalMediaResponse = self
-                .make_request_json(&url, &HttpMethod::Get(&params), Some((1, 0)))
+                .make_request_json(&url, &HttpMethod::Get(&params), None)
                 .await?;
-            if res.data.is_empty() {
-                return Ok(None);
+            let mut res_albums: Albums = res.try_into()?;
+            if !res_albums.0.is_empty() {
+                return Ok(Some(res_albums.0.remove(0)));
             }
-            let mut res_songs: Songs = res.try_into()?;
-            if res_songs.0.is_empty() {
-                return Ok(None);
-            }
-            return Ok(Some(res_songs.0.remove(0)));
         }
 
         let url = format!("{}/v1/search", Self::API_URL);
-        let mut queries = song.build_queries();
+        let mut queries = album.build_queries();
 
         while let Some(query) = queries.pop() {
             let params = json!({
                 "countryCode": self.country_code,
                 "query": query,
-                "type": "TRACKS",
+                "type": "ALBUMS",
             });
-            let res: TidalSearchResponse = self
+            let res: TidalAlbumSearchResponse = self
                 .make_request_json(&url, &HttpMethod::Get(&params), Some((3, 0)))
                 .await?;
-            let res_songs: Songs = res.try_into()?;
+            let res_albums: Albums = res.try_into()?;
             // iterate over top 3 results
-            for res_song in res_songs.0.into_iter().take(3) {
-                if song.compare(&res_song) {
-                    return Ok(Some(res_song));
+            for res_album in res_albums.0.into_iter().take(3) {
+                if album.compare(&res_album) {
+                    return Ok(Some(res_album));
                 }
             }
         }
         Ok(None)
     }
 
-    async fn add_likes(&self, songs: &[Song]) -> Result<()> {
-        if songs.is_empty() {
-            return Ok(());
-        }
-
+    async fn add_saved_albums(&self, albums: &[Album]) -> Result<()> {
         let url = format!(
-            "{}/v1/users/{}/favorites/tracks",
+            "{}/v1/users/{}/favorites/albums",
             Self::API_URL,
             self.user_id
         );
-        let tracks = songs.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+        let ids = albums
+            .iter()
+            .filter_map(|a| a.id.as_deref())
+            .collect::<Vec<_>>();
 
-        // NOTE: we get error 500 if we like too much songs at once
-        for tracks_chunk in tracks.chunks(100) {
+        for ids_chunk in ids.chunks(100) {
             let params = json!({
                 "countryCode": self.country_code,
-                "trackIds": tracks_chunk.join(","),
+                "albumIds": ids_chunk.join(","),
                 "onArtifactNotFound": "FAIL",
             });
             let () = self
@@ -424,19 +736,128 @@ impl MusicApi for TidalApi {
         Ok(())
     }
 
-    async fn get_likes(&self) -> Result<Vec<Song>> {
+    async fn get_followed_artists(&self) -> Result<Vec<Artist>> {
         let url = format!(
-            "{}/v1/users/{}/favorites/tracks",
+            "{}/v1/users/{}/favorites/artists",
             Self::API_URL,
             self.user_id
         );
         let params = json!({
             "countryCode": self.country_code,
         });
-        let res: TidalPageResponse<TidalSongItemResponse> = self
-            .paginated_request(&url, &HttpMethod::Get(&params), 1000)
+        let res: TidalPageResponse<TidalArtistItemResponse> = self
+            .paginated_request(&url, &HttpMethod::Get(&params), 100)
+            .await?;
+        let artists: Artists = res.try_into()?;
+        Ok(artists.0)
+    }
+
+    async fn search_artist_query(&self, query: &str, limit: usize) -> Result<Vec<Artist>> {
+        let url = format!("{}/v1/search", Self::API_URL);
+        let params = json!({
+            "countryCode": self.country_code,
+            "query": query,
+            "type": "ARTISTS",
+        });
+        let res: TidalArtistSearchResponse = self
+            .make_request_json(&url, &HttpMethod::Get(&params), Some((limit, 0)))
+            .await?;
+        let res_artists: Artists = res.try_into()?;
+        Ok(res_artists.0.into_iter().take(limit).collect())
+    }
+
+    async fn get_artist_top_songs(&self, artist: &Artist) -> Result<Vec<Song>> {
+        let Some(id) = &artist.id else {
+            return Ok(vec![]);
+        };
+        let url = format!("{}/v1/artists/{}/toptracks", Self::API_URL, id);
+        let params = json!({
+            "countryCode": self.country_code,
+        });
+        let res: TidalPageResponse<TidalSongResponse> = self
+            .make_request_json(&url, &HttpMethod::Get(&params), Some((10, 0)))
             .await?;
         let songs: Songs = res.try_into()?;
         Ok(songs.0)
     }
+
+    async fn follow_artists(&self, artists: &[Artist]) -> Result<()> {
+        let url = format!(
+            "{}/v1/users/{}/favorites/artists",
+            Self::API_URL,
+            self.user_id
+        );
+        let ids = artists
+            .iter()
+            .filter_map(|a| a.id.as_deref())
+            .collect::<Vec<_>>();
+
+        for ids_chunk in ids.chunks(100) {
+            let params = json!({
+                "countryCode": self.country_code,
+                "artistIds": ids_chunk.join(","),
+                "onArtifactNotFound": "FAIL",
+            });
+            let () = self
+                .make_request_json(&url, &HttpMethod::Post(&params), None)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_tracks_bisect() {
+        let songs: Vec<Song> = (0..250)
+            .map(|i| Song::test(MusicApiType::Spotify, &i.to_string(), "Song"))
+            .collect();
+        let requests = Mutex::new(vec![]);
+        let added = Mutex::new(vec![]);
+        let failed = TidalApi::add_tracks_bisect(&songs, |chunk: &[Song]| {
+            requests.lock().unwrap().push(chunk.len());
+            let res = if chunk.iter().any(|s| s.id == "142") {
+                Err(eyre!("Invalid HTTP status: 404 Not Found"))
+            } else {
+                added
+                    .lock()
+                    .unwrap()
+                    .extend(chunk.iter().map(|s| s.id.clone()));
+                Ok(())
+            };
+            async { res }
+        })
+        .await
+        .unwrap();
+
+        let failed: Vec<&str> = failed.iter().map(|(s, _)| s.id.as_str()).collect();
+        assert_eq!(failed, ["142"]);
+        let requests = requests.into_inner().unwrap();
+        assert!(
+            requests
+                .iter()
+                .all(|&len| len <= TidalApi::PLAYLIST_CHUNK_SIZE)
+        );
+        // the other songs are all added, in order
+        let expected: Vec<String> = (0..250)
+            .filter(|&i| i != 142)
+            .map(|i| i.to_string())
+            .collect();
+        assert_eq!(added.into_inner().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_add_tracks_bisect_aborts() {
+        let songs = vec![Song::test(MusicApiType::Spotify, "1", "Song"); 2];
+        let res = TidalApi::add_tracks_bisect(&songs, |_: &[Song]| async {
+            Err(eyre!("Invalid HTTP status: 412 Precondition Failed"))
+        })
+        .await;
+        assert!(res.is_err());
+    }
 }
@@ -29,11 +29,15 @@ pub struct TidalPageResponse<T> {
 pub struct TidalPlaylistResponse {
     pub uuid: String,
     pub title: String,
+    pub description: Option<String>,
+    pub public_playlist: Option<bool>,
+    pub square_image: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TidalSongItemResponse {
+    pub created: Option<String>,
     pub item: TidalSongResponse,
 }
 
@@ -46,6 +50,9 @@ pub struct TidalSongResponse {
     pub duration: usize,
     pub artists: Vec<TidalArtistResponse>,
     pub album: Option<TidalAlbumResponse>,
+    // e.g. "Live" or "2011 Remaster", not included in the title
+    pub version: Option<String>,
+    pub explicit: Option<bool>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -62,11 +69,40 @@ pub struct TidalArtistResponse {
     pub name: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct TidalArtistItemResponse {
+    pub item: TidalArtistResponse,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TidalArtistSearchResponse {
+    pub artists: TidalPageResponse<TidalArtistResponse>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct TidalSearchResponse {
     pub tracks: TidalPageResponse<TidalSongResponse>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct TidalAlbumItemResponse {
+    pub item: TidalFullAlbumResponse,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TidalFullAlbumResponse {
+    pub id: usize,
+    pub title: String,
+    pub artists: Vec<TidalArtistResponse>,
+    pub upc: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TidalAlbumSearchResponse {
+    pub albums: TidalPageResponse<TidalFullAlbumResponse>,
+}
+
 ////////////
 // V2 API //
 ////////////
@@ -83,6 +119,34 @@ pub struct TidalPlaylistDataResponse {
     pub uuid: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct TidalArtworkResponse {
+    pub data: TidalArtworkData,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TidalArtworkData {
+    pub id: String,
+    pub attributes: TidalArtworkAttributes,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TidalArtworkAttributes {
+    pub source_file: Option<TidalArtworkSourceFile>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TidalArtworkSourceFile {
+    pub upload_link: TidalArtworkUploadLink,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TidalArtworkUploadLink {
+    pub href: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct TidalMediaResponseSingle {
     pub data: TidalMediaData,
@@ -116,6 +180,8 @@ pub struct TidalMediaAttributes {
     pub duration: Option<String>,
     pub barcode_id: Option<String>,
     pub popularity: Option<f32>,
+    pub version: Option<String>,
+    pub explicit: Option<bool>,
 
     // user attributes
     pub username: Option<String>,
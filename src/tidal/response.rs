@@ -2,12 +2,16 @@ use color_eyre::eyre::{Error, OptionExt, Result, eyre};
 use tracing::error;
 
 use super::model::{
-    TidalMediaData, TidalMediaResponse, TidalPageResponse, TidalPlaylistResponse,
-    TidalSearchResponse, TidalSongItemResponse, TidalSongResponse,
+    TidalAlbumItemResponse, TidalAlbumSearchResponse, TidalArtistItemResponse, TidalArtistResponse,
+    TidalArtistSearchResponse, TidalFullAlbumResponse, TidalMediaData, TidalMediaRelationshipData,
+    TidalMediaResponse, TidalPageResponse, TidalPlaylistResponse, TidalSearchResponse,
+    TidalSongItemResponse, TidalSongResponse,
 };
 use crate::{
-    music_api::{Album, Artist, MusicApiType, Playlist, Playlists, Song, Songs},
-    utils::clean_isrc,
+    music_api::{
+        Album, Albums, Artist, Artists, MusicApiType, Playlist, Playlists, Song, SongVersion, Songs,
+    },
+    utils::{clean_isrc, clean_upc, parse_timestamp},
 };
 
 // multiples
@@ -37,13 +41,14 @@ impl TryInto<Songs> for TidalPageResponse<TidalSongItemResponse> {
     fn try_into(self) -> Result<Songs, Self::Error> {
         let mut res = vec![];
         for item in self.items {
-            let song = match item.item.try_into() {
+            let mut song: Song = match item.item.try_into() {
                 Ok(s) => s,
                 Err(e) => {
                     error!("failed to parse song in response, skipping it: {}", e);
                     continue;
                 }
             };
+            song.added_at = item.created.as_deref().and_then(parse_timestamp);
             res.push(song);
         }
         Ok(Songs(res))
@@ -53,9 +58,17 @@ impl TryInto<Songs> for TidalPageResponse<TidalSongItemResponse> {
 impl TryInto<Songs> for TidalSearchResponse {
     type Error = Error;
 
+    fn try_into(self) -> Result<Songs, Self::Error> {
+        self.tracks.try_into()
+    }
+}
+
+impl TryInto<Songs> for TidalPageResponse<TidalSongResponse> {
+    type Error = Error;
+
     fn try_into(self) -> Result<Songs, Self::Error> {
         let mut res = vec![];
-        for track in self.tracks.items {
+        for track in self.items {
             match track.try_into() {
                 Ok(s) => res.push(s),
                 Err(e) => {
@@ -70,19 +83,95 @@ impl TryInto<Songs> for TidalSearchResponse {
     }
 }
 
+impl TryInto<Albums> for TidalPageResponse<TidalAlbumItemResponse> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Albums, Self::Error> {
+        Ok(Albums(
+            self.items.into_iter().map(|i| i.item.into()).collect(),
+        ))
+    }
+}
+
+impl TryInto<Albums> for TidalAlbumSearchResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Albums, Self::Error> {
+        Ok(Albums(
+            self.albums.items.into_iter().map(Into::into).collect(),
+        ))
+    }
+}
+
+impl TryInto<Artists> for TidalPageResponse<TidalArtistItemResponse> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Artists, Self::Error> {
+        Ok(Artists(
+            self.items.into_iter().map(|i| i.item.into()).collect(),
+        ))
+    }
+}
+
+impl TryInto<Artists> for TidalArtistSearchResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Artists, Self::Error> {
+        Ok(Artists(
+            self.artists.items.into_iter().map(Into::into).collect(),
+        ))
+    }
+}
+
+impl TryInto<Albums> for TidalMediaResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Albums, Self::Error> {
+        let included = self.included.unwrap_or_default();
+        let mut albums = Vec::new();
+        for data in self.data {
+            match media_data_to_album(data, &included) {
+                Ok(a) => albums.push(a),
+                Err(e) => {
+                    error!("failed to parse album in response, skipping it: {}", e);
+                }
+            }
+        }
+        Ok(Albums(albums))
+    }
+}
+
 // singles
 
 impl TryInto<Playlist> for TidalPlaylistResponse {
     type Error = Error;
     fn try_into(self) -> Result<Playlist, Self::Error> {
+        // image ids are UUIDs mapped to the resources path
+        let cover_url = self.square_image.map(|image| {
+            format!(
+                "https://resources.tidal.com/images/{}/750x750.jpg",
+                image.replace('-', "/")
+            )
+        });
         Ok(Playlist {
             id: self.uuid,
             name: self.title.trim().to_string(),
+            description: self.description.filter(|d| !d.is_empty()),
+            public: self.public_playlist,
+            cover_url,
             songs: vec![],
         })
     }
 }
 
+/// Tidal exposes the song version separately from its title
+fn versioned_name_parse(title: &str, version: Option<&str>) -> Vec<SongVersion> {
+    match version {
+        Some(version) => SongVersion::parse(&format!("{} ({})", title, version)),
+        None => SongVersion::parse(title),
+    }
+}
+
 impl TryInto<Song> for TidalSongResponse {
     type Error = Error;
     fn try_into(self) -> Result<Song, Self::Error> {
@@ -92,15 +181,11 @@ impl TryInto<Song> for TidalSongResponse {
         let album = Album {
             id: Some(album.id.to_string()),
             name: album.title,
+            artists: vec![],
+            upc: None,
         };
-        let artists = self
-            .artists
-            .into_iter()
-            .map(|a| Artist {
-                id: Some(a.id.to_string()),
-                name: a.name,
-            })
-            .collect();
+        let artists = self.artists.into_iter().map(Into::into).collect();
+        let versions = versioned_name_parse(&self.title, self.version.as_deref());
 
         Ok(Song {
             source: MusicApiType::Tidal,
@@ -111,10 +196,33 @@ impl TryInto<Song> for TidalSongResponse {
             album: Some(album),
             artists,
             duration_ms: self.duration * 1000,
+            versions,
+            explicit: self.explicit,
+            added_at: None,
         })
     }
 }
 
+impl From<TidalFullAlbumResponse> for Album {
+    fn from(album: TidalFullAlbumResponse) -> Self {
+        Album {
+            id: Some(album.id.to_string()),
+            name: album.title,
+            artists: album.artists.into_iter().map(Into::into).collect(),
+            upc: clean_upc(album.upc),
+        }
+    }
+}
+
+impl From<TidalArtistResponse> for Artist {
+    fn from(artist: TidalArtistResponse) -> Self {
+        Artist {
+            id: Some(artist.id.to_string()),
+            name: artist.name,
+        }
+    }
+}
+
 impl TryInto<Songs> for TidalMediaResponse {
     type Error = Error;
 
@@ -170,7 +278,7 @@ fn media_data_to_song(data: TidalMediaData, included: &[TidalMediaData]) -> Resu
         + second as usize * 1000
         + millisecond as usize;
 
-    let mut artists = Vec::new();
+    let mut artists = vec![];
     let mut album = None;
 
     if let Some(album_rel) = data
@@ -197,6 +305,8 @@ fn media_data_to_song(data: TidalMediaData, included: &[TidalMediaData]) -> Resu
         album = Some(Album {
             id: Some(album_data.id.clone()),
             name: title,
+            artists: vec![],
+            upc: None,
         });
     }
     if let Some(artists_rel) = data
@@ -205,31 +315,64 @@ fn media_data_to_song(data: TidalMediaData, included: &[TidalMediaData]) -> Resu
         .and_then(|r| r.artists.as_ref())
         .and_then(|a| a.data.as_ref())
     {
-        for artist_rel in artists_rel {
-            let artist_data = included
-                .iter()
-                .find(|i| i.id == artist_rel.id)
-                .ok_or_eyre("missing song artist data")?;
-            let name = artist_data
-                .attributes
-                .name
-                .clone()
-                .ok_or_eyre("missing song artist name")?;
-            artists.push(Artist {
-                id: Some(artist_data.id.clone()),
-                name,
-            });
-        }
+        artists = media_artists(artists_rel, included)?;
     }
 
+    let name = data.attributes.title.ok_or_eyre("missing song title")?;
+    let versions = versioned_name_parse(&name, data.attributes.version.as_deref());
     Ok(Song {
         source: MusicApiType::Tidal,
         id: data.id,
         sid: None,
         isrc: clean_isrc(data.attributes.isrc),
-        name: data.attributes.title.ok_or_eyre("missing song title")?,
+        name,
         album,
         artists,
         duration_ms: duration,
+        versions,
+        explicit: data.attributes.explicit,
+        added_at: None,
     })
 }
+
+fn media_data_to_album(data: TidalMediaData, included: &[TidalMediaData]) -> Result<Album> {
+    let mut artists = vec![];
+    if let Some(artists_rel) = data
+        .relationships
+        .as_ref()
+        .and_then(|r| r.artists.as_ref())
+        .and_then(|a| a.data.as_ref())
+    {
+        artists = media_artists(artists_rel, included)?;
+    }
+
+    Ok(Album {
+        id: Some(data.id),
+        name: data.attributes.title.ok_or_eyre("missing album title")?,
+        artists,
+        upc: clean_upc(data.attributes.barcode_id),
+    })
+}
+
+fn media_artists(
+    artists_rel: &[TidalMediaRelationshipData],
+    included: &[TidalMediaData],
+) -> Result<Vec<Artist>> {
+    let mut artists = vec![];
+    for artist_rel in artists_rel {
+        let artist_data = included
+            .iter()
+            .find(|i| i.id == artist_rel.id)
+            .ok_or_eyre("missing artist data")?;
+        let name = artist_data
+            .attributes
+            .name
+            .clone()
+            .ok_or_eyre("missing artist name")?;
+        artists.push(Artist {
+            id: Some(artist_data.id.clone()),
+            name,
+        });
+    }
+    Ok(artists)
+}
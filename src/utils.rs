@@ -1,10 +1,21 @@
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
 use color_eyre::Result;
 use regex::Regex;
 use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use time::format_description::well_known::{Iso8601, Rfc3339};
 use tracing::error;
+use unicode_normalization::UnicodeNormalization;
 
 use crate::{ConfigArgs, music_api::Song};
 
+static CREDITS_SEP_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s+(?:feat\.?|ft\.|featuring|&|x)\s+|\s*,\s*").unwrap());
+static HTTP_STATUS_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Invalid HTTP status: (\d{3})").unwrap());
+
 pub fn clean_enclosure(name: &str, start_tag: char, end_tag: char) -> String {
     if name.contains(start_tag) {
         let mut res = vec![];
@@ -33,17 +44,43 @@ pub fn clean_enclosure(name: &str, start_tag: char, end_tag: char) -> String {
     name.to_string()
 }
 
+/// Fold the Unicode variants of common punctuation signs to their ASCII counterpart
+fn fold_punctuation(c: char) -> char {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' | '`' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '\u{AB}' | '\u{BB}' => '"',
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+        c if c.is_whitespace() => ' ',
+        c => c,
+    }
+}
+
+fn is_diacritic(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}')
+}
+
+/// Compatibility decomposition without diacritics, case folding and punctuation folding
+pub fn normalize_unicode(name: &str) -> String {
+    // NOTE: other combining marks, e.g. the Japanese voicing marks, are recomposed
+    let name: String = name.nfkd().filter(|c| !is_diacritic(*c)).nfc().collect();
+    let name = caseless::default_case_fold_str(&name);
+    name.chars().map(fold_punctuation).collect()
+}
+
+/// ASCII transliteration of a cleaned name, e.g. to compare non-latin scripts with their
+/// romanization
+pub fn transliterate(name: &str) -> String {
+    deunicode::deunicode(name).to_lowercase()
+}
+
 pub fn generic_name_clean(name: &str) -> String {
-    let mut name = name.to_lowercase();
-    let replaces = [
-        ("'", ""),
-        ("\"", ""),
-        (":", " "),
-        ("%", ""),
-        ("é", "e"),
-        ("è", "e"),
-        ("à", "a"),
-    ];
+    let mut name = normalize_unicode(name);
+    let replaces = [("'", ""), ("\"", ""), (":", " "), ("%", "")];
     for (a, b) in replaces {
         name = name.replace(a, b);
     }
@@ -53,7 +90,18 @@ pub fn generic_name_clean(name: &str) -> String {
     }
     let name = clean_enclosure(&name, '(', ')');
     let name = clean_enclosure(&name, '[', ']');
-    name.trim_end().to_string()
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Split a credits string such as "A feat. B & C" into individual cleaned artist names
+pub fn split_artist_credits(credits: &str) -> Vec<String> {
+    let credits = generic_name_clean(credits);
+    CREDITS_SEP_RE
+        .split(&credits)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 #[inline]
@@ -69,6 +117,17 @@ pub fn clean_isrc(isrc: Option<String>) -> Option<String> {
     None
 }
 
+#[inline]
+pub fn clean_upc(upc: Option<String>) -> Option<String> {
+    let upc = upc?;
+    let upc = upc.trim();
+    if upc.is_empty() || !upc.chars().all(|c| c.is_ascii_digit()) {
+        error!("invalid UPC code found: {}, ignoring it", upc);
+        return None;
+    }
+    Some(upc.to_string())
+}
+
 pub fn dedup_songs(songs: &mut Vec<Song>) -> bool {
     let mut seen = std::collections::HashSet::new();
     let mut dups = false;
@@ -111,10 +170,101 @@ where
     Ok(res)
 }
 
+/// Spaces out requests shared by concurrent tasks
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Wait for the next request slot
+    pub async fn wait(&self) {
+        let wait = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |s| s.max(now));
+            *next_slot = Some(slot + self.interval);
+            slot - now
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// HTTP client builder with the settings shared by all the requests, e.g. the proxy
+pub fn client_builder(config: &ConfigArgs) -> Result<reqwest::ClientBuilder> {
+    let mut client = reqwest::Client::builder().cookie_store(true);
+    if let Some(proxy) = &config.proxy {
+        client = client
+            .proxy(reqwest::Proxy::all(proxy)?)
+            .danger_accept_invalid_certs(true);
+    }
+    Ok(client)
+}
+
+/// HTTP status of the failed request, from the reqwest error or the invalid status message
+pub fn error_status(err: &color_eyre::eyre::Report) -> Option<u16> {
+    err.chain().find_map(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.status().map(|s| s.as_u16());
+        }
+        HTTP_STATUS_RE
+            .captures(&cause.to_string())
+            .and_then(|c| c[1].parse::<u16>().ok())
+    })
+}
+
+/// HTTP client without credentials, for the requests to other hosts, e.g. covers
+pub fn media_client(config: &ConfigArgs) -> Result<reqwest::Client> {
+    Ok(client_builder(config)?.build()?)
+}
+
+/// Whether the error is worth retrying: network failures, rate limiting and server errors
+pub fn is_transient_error(err: &color_eyre::eyre::Report) -> bool {
+    let network = err.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout() || e.is_connect())
+    });
+    network || error_status(err).is_some_and(|s| s == 429 || (500..600).contains(&s))
+}
+
+/// UNIX timestamp of an ISO 8601 datetime, e.g. `2024-03-05T12:34:56Z`
+pub fn parse_timestamp(datetime: &str) -> Option<i64> {
+    OffsetDateTime::parse(datetime, &Iso8601::PARSING)
+        .ok()
+        .map(OffsetDateTime::unix_timestamp)
+}
+
+/// ISO 8601 UTC datetime of a UNIX timestamp
+pub fn format_timestamp(timestamp: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|datetime| datetime.format(&Rfc3339).ok())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_timestamps() {
+        let spotify = parse_timestamp("2024-03-05T12:34:56Z").unwrap();
+        assert_eq!(spotify, 1_709_642_096);
+        assert_eq!(format_timestamp(spotify), "2024-03-05T12:34:56Z");
+        let tidal = parse_timestamp("2021-05-12T20:24:39.873+0200").unwrap();
+        assert_eq!(format_timestamp(tidal), "2021-05-12T18:24:39Z");
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert!(parse_timestamp("not a date").is_none());
+    }
+
     #[test]
     fn test_clean_enclosure() {
         let name =
@@ -126,4 +276,87 @@ mod tests {
         let res = clean_enclosure(name, '(', ')');
         assert_eq!(res, "test  test");
     }
+
+    #[test]
+    fn test_generic_name_clean_multilingual() {
+        let corpus = [
+            ("Beyoncé", "beyonce"),
+            ("Sigur Rós", "sigur ros"),
+            ("Motörhead", "motorhead"),
+            ("Mañana (feat. Niña Pastori)", "manana"),
+            ("Ｔｏｋｙｏ　Ｄｒｉｆｔ", "tokyo drift"),
+            ("Don’t Stop Me Now", "dont stop me now"),
+            ("Señorita – Remix", "senorita - remix"),
+            ("Straße", "strasse"),
+            ("Čaj za dva: Live", "caj za dva live"),
+            ("Ｌｏｖｅ　ｓｏ　ｓｏｆｔ", "love so soft"),
+        ];
+        for (name, expected) in corpus {
+            assert_eq!(generic_name_clean(name), expected, "{}", name);
+        }
+        // the same title written differently on two platforms
+        assert_eq!(
+            generic_name_clean("“Heroes” – 2017 Remaster"),
+            generic_name_clean("\"Heroes\" - 2017 Remaster")
+        );
+    }
+
+    #[test]
+    fn test_transliterate() {
+        let corpus = [
+            ("Кино", "kino"),
+            ("Группа крови", "gruppa krovi"),
+            ("Μαμά", "mama"),
+            ("Ænima", "aenima"),
+            ("ポケモン", "pokemon"),
+        ];
+        for (name, expected) in corpus {
+            assert_eq!(
+                transliterate(&generic_name_clean(name)),
+                expected,
+                "{}",
+                name
+            );
+        }
+        // the search queries keep the original script
+        assert_eq!(generic_name_clean("Кино"), "кино");
+    }
+
+    #[test]
+    fn test_split_artist_credits() {
+        assert_eq!(
+            split_artist_credits("Daft Punk feat. Pharrell Williams & Nile Rodgers"),
+            ["daft punk", "pharrell williams", "nile rodgers"]
+        );
+        assert_eq!(
+            split_artist_credits("Skrillex x Fred again.., Flowdan"),
+            ["skrillex", "fred again..", "flowdan"]
+        );
+        assert_eq!(split_artist_credits("AC/DC"), ["ac/dc"]);
+        assert_eq!(split_artist_credits("Malcolm X"), ["malcolm x"]);
+    }
+
+    #[test]
+    fn test_is_transient_error() {
+        use color_eyre::eyre::eyre;
+
+        let status = reqwest::StatusCode::BAD_GATEWAY;
+        assert!(is_transient_error(&eyre!(
+            "Invalid HTTP status: {}",
+            status
+        )));
+        let status = reqwest::StatusCode::TOO_MANY_REQUESTS;
+        assert!(is_transient_error(&eyre!(
+            "Invalid HTTP status: {}",
+            status
+        )));
+        let status = reqwest::StatusCode::NOT_FOUND;
+        assert!(!is_transient_error(&eyre!(
+            "Invalid HTTP status: {}",
+            status
+        )));
+        assert!(!is_transient_error(&eyre!("missing field `id`")));
+        let err = eyre!("Invalid HTTP status: {}", status).wrap_err("failed to add songs");
+        assert_eq!(error_status(&err), Some(404));
+    }
 }
@@ -6,24 +6,28 @@ use std::fmt::Write;
 use std::io::Read;
 use std::path::PathBuf;
 use std::sync::LazyLock;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use color_eyre::eyre::{Result, eyre};
+use futures::StreamExt;
+use indicatif::{ProgressBar, ProgressStyle};
 use model::{YtMusicAddLikeResponse, YtMusicOAuthDeviceRes};
 use reqwest::header::{HeaderMap, HeaderName};
 use serde::de::DeserializeOwned;
 use serde_json::json;
-use tracing::info;
+use tracing::{debug, info};
 
 use self::model::{YtMusicContinuationResponse, YtMusicPlaylistEditResponse, YtMusicResponse};
 use crate::ConfigArgs;
+use crate::matcher::MatchPath;
 use crate::music_api::{
-    MusicApi, MusicApiType, OAuthRefreshToken, OAuthToken, PLAYLIST_DESC, Playlist, Playlists,
-    Song, Songs,
+    Album, Albums, Artist, Artists, MusicApi, MusicApiType, OAuthRefreshToken, OAuthToken,
+    Playlist, Playlists, Song, Songs,
 };
-use crate::utils::debug_response_json;
+use crate::utils::{RateLimiter, client_builder, debug_response_json};
 use crate::yt_music::model::{YtMusicPlaylistCreateResponse, YtMusicPlaylistDeleteResponse};
-use crate::yt_music::response::{SearchSongUnique, SearchSongs};
+use crate::yt_music::response::{SearchAlbums, SearchSongUnique, SearchSongs};
 
 static CONTEXT: LazyLock<serde_json::Value> = LazyLock::new(|| {
     json!({
@@ -51,6 +55,9 @@ impl YtMusicApi {
     const OAUTH_GRANT_TYPE: &'static str = "http://oauth.net/grant_type/device/1.0";
     const OAUTH_USER_AGENT: &'static str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:88.0) Gecko/20100101 Firefox/88.0 Cobalt/Version";
     const RES_DEBUG_FILENAME: &'static str = MusicApiType::YtMusic.short_name();
+    // NOTE: liking too fast gets the requests throttled
+    const RATE_CONCURRENCY: usize = 4;
+    const RATE_INTERVAL: Duration = Duration::from_millis(200);
 
     pub async fn new_oauth(
         client_id: &str,
@@ -90,15 +97,7 @@ impl YtMusicApi {
             format!("Bearer {}", token.access_token).parse()?,
         );
 
-        let mut client = reqwest::Client::builder()
-            .cookie_store(true)
-            .default_headers(headers);
-        if let Some(proxy) = &config.proxy {
-            client = client
-                .proxy(reqwest::Proxy::all(proxy)?)
-                .danger_accept_invalid_certs(true);
-        }
-        let client = client.build()?;
+        let client = client_builder(&config)?.default_headers(headers).build()?;
 
         Ok(YtMusicApi { client, config })
     }
@@ -203,22 +202,13 @@ impl YtMusicApi {
         headers.remove("accept-encoding");
         headers.remove("content-encoding");
 
-        let mut client = reqwest::ClientBuilder::new()
-            .cookie_store(true)
-            .default_headers(headers);
-
-        if let Some(proxy) = &config.proxy {
-            client = client
-                .proxy(reqwest::Proxy::all(proxy)?)
-                .danger_accept_invalid_certs(true);
-        }
-        let client = client.build()?;
+        let client = client_builder(&config)?.default_headers(headers).build()?;
 
         Ok(YtMusicApi { client, config })
     }
 
     fn build_endpoint(path: &str, ctoken: Option<&str>) -> String {
-        let mut endpoint = format!("{}{}{}", Self::BASE_API, path, Self::BASE_PARAMS,);
+        let mut endpoint = format!("{}{}{}", Self::BASE_API, path, Self::BASE_PARAMS);
         if let Some(c) = ctoken {
             std::write!(&mut endpoint, "&ctoken={c}&continuation={c}", c = c).unwrap();
         }
@@ -271,6 +261,83 @@ impl YtMusicApi {
         Ok(obj)
     }
 
+    /// Retrieve the playlist ID of an album, required to save it to the library
+    async fn get_album_playlist_id(&self, browse_id: &str) -> Result<String> {
+        let body = json!({ "browseId": browse_id });
+        let response: serde_json::Value = self.make_request("browse", &body, None).await?;
+        let url = response
+            .pointer("/microformat/microformatDataRenderer/urlCanonical")
+            .and_then(|u| u.as_str())
+            .ok_or(eyre!("No canonical url for album {}", browse_id))?;
+        let playlist_id = reqwest::Url::parse(url)?
+            .query_pairs()
+            .find(|(k, _)| k == "list")
+            .ok_or(eyre!("No playlist id for album {}", browse_id))?
+            .1
+            .to_string();
+        Ok(playlist_id)
+    }
+
+    /// Like or unlike the songs, there is no bulk endpoint so the requests are rate limited
+    /// and sent a few at a time. When `ordered`, each group of requests waits for the
+    /// previous one, so that the likes order follows the songs order
+    async fn rate_songs(
+        &self,
+        endpoint: &str,
+        songs: &[Song],
+        label: &str,
+        ordered: bool,
+    ) -> Result<()> {
+        if songs.is_empty() {
+            return Ok(());
+        }
+        let rate_limiter = RateLimiter::new(Self::RATE_INTERVAL);
+        let progress = ProgressBar::new(songs.len() as u64).with_style(
+            ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len} ({eta})")?
+                .progress_chars("=> "),
+        );
+        progress.set_message(label.to_string());
+
+        let group_size = if ordered {
+            Self::RATE_CONCURRENCY
+        } else {
+            songs.len()
+        };
+        for group in songs.chunks(group_size) {
+            let requests: Vec<_> = group
+                .iter()
+                .map(|song| self.rate_song(endpoint, song, &rate_limiter))
+                .collect();
+            let mut requests =
+                futures::stream::iter(requests).buffer_unordered(Self::RATE_CONCURRENCY);
+            while let Some(res) = requests.next().await {
+                if let Err(e) = res {
+                    progress.abandon();
+                    return Err(e);
+                }
+                progress.inc(1);
+            }
+        }
+        progress.finish_and_clear();
+        Ok(())
+    }
+
+    async fn rate_song(
+        &self,
+        endpoint: &str,
+        song: &Song,
+        rate_limiter: &RateLimiter,
+    ) -> Result<()> {
+        rate_limiter.wait().await;
+        let body = json!({
+            "target": {
+                "videoId": song.id,
+            }
+        });
+        let _: YtMusicAddLikeResponse = self.make_request(endpoint, &body, None).await?;
+        Ok(())
+    }
+
     pub fn clean_playlist_id(id: &str) -> String {
         if let Some(id) = id.strip_prefix("VL") {
             return id.to_string();
@@ -285,16 +352,25 @@ impl MusicApi for YtMusicApi {
         MusicApiType::YtMusic
     }
 
+    fn config(&self) -> &ConfigArgs {
+        &self.config
+    }
+
     fn country_code(&self) -> &'static str {
         // TODO: it seems impossible to get the country code from YtMusic
         "UNKNOWN"
     }
 
-    async fn create_playlist(&self, name: &str, public: bool) -> Result<Playlist> {
+    async fn create_playlist(
+        &self,
+        name: &str,
+        description: &str,
+        public: bool,
+    ) -> Result<Playlist> {
         let privacy_status = if public { "PUBLIC" } else { "PRIVATE" };
         let body = json!({
             "title": name,
-            "description": PLAYLIST_DESC,
+            "description": description,
             "privacyStatus": privacy_status,
         });
         let response: YtMusicPlaylistCreateResponse =
@@ -303,6 +379,9 @@ impl MusicApi for YtMusicApi {
         Ok(Playlist {
             id,
             name: name.to_string(),
+            description: Some(description.to_string()),
+            public: Some(public),
+            cover_url: None,
             songs: vec![],
         })
     }
@@ -393,49 +472,141 @@ impl MusicApi for YtMusicApi {
         Ok(())
     }
 
-    async fn search_song(&self, song: &Song) -> Result<Option<Song>> {
+    async fn update_playlist_details(
+        &self,
+        playlist: &mut Playlist,
+        description: &str,
+        public: bool,
+    ) -> Result<()> {
+        let privacy_status = if public { "PUBLIC" } else { "PRIVATE" };
+        let body = json!({
+            "playlistId": playlist.id,
+            "actions": [
+                {
+                    "action": "ACTION_SET_PLAYLIST_DESCRIPTION",
+                    "playlistDescription": description,
+                },
+                {
+                    "action": "ACTION_SET_PLAYLIST_PRIVACY",
+                    "playlistPrivacy": privacy_status,
+                },
+            ],
+        });
+        let response: YtMusicPlaylistEditResponse = self
+            .make_request("browse/edit_playlist", &body, None)
+            .await?;
+        if !response.success() {
+            return Err(eyre!("Error updating playlist details"));
+        }
+        playlist.description = Some(description.to_string());
+        playlist.public = Some(public);
+        Ok(())
+    }
+
+    async fn upload_playlist_cover(&self, _playlist: &mut Playlist, _jpeg: &[u8]) -> Result<()> {
+        Err(eyre!(
+            "uploading a playlist cover is not supported on Youtube Music"
+        ))
+    }
+
+    async fn search_song_match(&self, song: &Song) -> Result<Option<(Song, MatchPath)>> {
         if let Some(isrc) = &song.isrc {
+            if let Some(res_song) = self.search_isrc(song, isrc).await? {
+                return Ok(Some((res_song, MatchPath::Isrc)));
+            }
+            debug!("no ISRC match, falling back to fuzzy search: {}", song);
+        }
+        self.search_fuzzy(song, song.build_queries()).await
+    }
+
+    async fn search_isrc(&self, _song: &Song, isrc: &str) -> Result<Option<Song>> {
+        let body = json!({
+            "query": format!("\"{}\"", isrc),
+        });
+        let response = self
+            .make_request::<YtMusicResponse>("search", &body, None)
+            .await?;
+        let res_song: SearchSongUnique = response.try_into()?;
+        Ok(res_song.0.map(|mut res_song| {
+            res_song.isrc = Some(isrc.to_string());
+            res_song
+        }))
+    }
+
+    async fn search_query(&self, query: &str, limit: usize) -> Result<Vec<Song>> {
+        let ignore_spelling = "AUICCAFqDBAOEAoQAxAEEAkQBQ%3D%3D";
+        let params = format!("EgWKAQ{}{}", "II", ignore_spelling);
+        let body = json!({
+            "query": query,
+            "params": params,
+        });
+        let response = self
+            .make_request::<YtMusicResponse>("search", &body, None)
+            .await?;
+        let res_songs: SearchSongs = response.try_into()?;
+        Ok(res_songs.0.into_iter().take(limit).collect())
+    }
+
+    async fn add_likes(&self, songs: &[Song]) -> Result<()> {
+        // only the songs with a like date have an order to keep
+        let (undated, dated): (Vec<Song>, Vec<Song>) =
+            songs.iter().cloned().partition(|s| s.added_at.is_none());
+        self.rate_songs("like/like", &undated, "liking songs", false)
+            .await?;
+        self.rate_songs("like/like", &dated, "liking songs", true)
+            .await
+    }
+
+    async fn remove_likes(&self, songs: &[Song]) -> Result<()> {
+        self.rate_songs("like/removelike", songs, "removing likes", false)
+            .await
+    }
+
+    async fn get_likes(&self) -> Result<Vec<Song>> {
+        let songs = self.get_playlist_songs("LM").await?;
+        Ok(songs)
+    }
+
+    async fn get_saved_albums(&self) -> Result<Vec<Album>> {
+        let body = json!({ "browseId": "FEmusic_liked_albums" });
+        let response = self.paginated_request("browse", &body).await?;
+        let albums: Albums = response.try_into()?;
+        Ok(albums.0)
+    }
+
+    async fn search_album(&self, album: &Album) -> Result<Option<Album>> {
+        let ignore_spelling = "AUICCAFqDBAOEAoQAxAEEAkQBQ%3D%3D";
+        let params = format!("EgWKAQ{}{}", "IY", ignore_spelling);
+        let mut queries = album.build_queries();
+        while let Some(query) = queries.pop() {
             let body = json!({
-                "query": format!("\"{}\"", isrc),
+                "query": query,
+                "params": params,
             });
             let response = self
                 .make_request::<YtMusicResponse>("search", &body, None)
                 .await?;
-            let res_song: SearchSongUnique = response.try_into()?;
-            if let Some(mut res_song) = res_song.0 {
-                res_song.isrc = Some(isrc.clone());
-                return Ok(Some(res_song));
-            }
-        } else {
-            let ignore_spelling = "AUICCAFqDBAOEAoQAxAEEAkQBQ%3D%3D";
-            let params = format!("EgWKAQ{}{}", "II", ignore_spelling);
-            let mut queries = song.build_queries();
-            while let Some(query) = queries.pop() {
-                let body = json!({
-                    "query": query,
-                    "params": params,
-                });
-                let response = self
-                    .make_request::<YtMusicResponse>("search", &body, None)
-                    .await?;
-                let res_songs: SearchSongs = response.try_into()?;
-                // iterate over top 3 results
-                for res_song in res_songs.0.into_iter().take(3) {
-                    if song.compare(&res_song) {
-                        return Ok(Some(res_song));
-                    }
+            let res_albums: SearchAlbums = response.try_into()?;
+            // iterate over top 3 results
+            for res_album in res_albums.0.into_iter().take(3) {
+                if album.compare(&res_album) {
+                    return Ok(Some(res_album));
                 }
             }
         }
         Ok(None)
     }
 
-    async fn add_likes(&self, songs: &[Song]) -> Result<()> {
-        // TODO: find a way to bulk-like
-        for song in songs {
+    async fn add_saved_albums(&self, albums: &[Album]) -> Result<()> {
+        // NOTE: saving an album is done by liking its playlist
+        for album in albums {
+            let Some(browse_id) = &album.id else {
+                continue;
+            };
+            let playlist_id = self.get_album_playlist_id(browse_id).await?;
             let body = json!({
                 "target": {
-                    "videoId": song.id,
+                    "playlistId": playlist_id,
                 }
             });
             let _: YtMusicAddLikeResponse = self.make_request("like/like", &body, None).await?;
@@ -443,9 +614,43 @@ impl MusicApi for YtMusicApi {
         Ok(())
     }
 
-    async fn get_likes(&self) -> Result<Vec<Song>> {
-        let songs = self.get_playlist_songs("LM").await?;
-        Ok(songs)
+    async fn get_followed_artists(&self) -> Result<Vec<Artist>> {
+        let body = json!({ "browseId": "FEmusic_library_corpus_artists" });
+        let response = self.paginated_request("browse", &body).await?;
+        let artists: Artists = response.try_into()?;
+        Ok(artists.0)
+    }
+
+    async fn search_artist_query(&self, query: &str, limit: usize) -> Result<Vec<Artist>> {
+        let ignore_spelling = "AUICCAFqDBAOEAoQAxAEEAkQBQ%3D%3D";
+        let params = format!("EgWKAQ{}{}", "Ig", ignore_spelling);
+        let body = json!({
+            "query": query,
+            "params": params,
+        });
+        let response = self
+            .make_request::<YtMusicResponse>("search", &body, None)
+            .await?;
+        let res_artists: Artists = response.try_into()?;
+        Ok(res_artists.0.into_iter().take(limit).collect())
+    }
+
+    async fn get_artist_top_songs(&self, _artist: &Artist) -> Result<Vec<Song>> {
+        // NOTE: songs don't expose their ISRC, artists are matched by name only
+        Ok(vec![])
+    }
+
+    async fn follow_artists(&self, artists: &[Artist]) -> Result<()> {
+        // NOTE: following an artist is done by subscribing to its channel
+        let channel_ids: Vec<&str> = artists.iter().filter_map(|a| a.id.as_deref()).collect();
+        if channel_ids.is_empty() {
+            return Ok(());
+        }
+        let body = json!({ "channelIds": channel_ids });
+        let _: serde_json::Value = self
+            .make_request("subscription/subscribe", &body, None)
+            .await?;
+        Ok(())
     }
 }
 
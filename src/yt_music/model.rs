@@ -122,9 +122,13 @@ impl YtMusicResponse {
     }
 
     pub fn get_music_playlist_shelf_renderer(&mut self) -> Option<&mut MusicPlaylistShelfRenderer> {
-        self.get_section_renderer_content()?
+        let content = self.get_section_renderer_content()?;
+        // library listings, e.g. the artists subscriptions, use a plain music shelf with
+        // the same continuations
+        content
             .music_playlist_shelf_renderer
             .as_mut()
+            .or(content.music_shelf_renderer.as_mut())
     }
 
     pub fn get_continuation(&mut self) -> Option<String> {
@@ -223,6 +227,7 @@ pub struct MusicPlaylistShelfRendererContent {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MusicResponsiveListItemRenderer {
+    pub navigation_endpoint: Option<NavigationEndpoint>,
     pub menu: Option<Menu>,
     pub overlay: Option<Overlay>,
     pub flex_columns: Vec<FlexColumn>,
@@ -241,6 +246,17 @@ impl MusicResponsiveListItemRenderer {
         Some(self.playlist_item_data.as_ref()?.video_id.clone())
     }
 
+    pub fn get_browse_id(&self) -> Option<String> {
+        Some(
+            self.navigation_endpoint
+                .as_ref()?
+                .browse_endpoint
+                .as_ref()?
+                .browse_id
+                .clone(),
+        )
+    }
+
     pub fn get_col_run_text(&self, idx: usize, run_i: usize, flex: bool) -> Option<String> {
         Some(self.get_col_runs(idx, flex)?.get(run_i)?.get_text())
     }
@@ -315,8 +331,23 @@ pub struct MusicTwoRowItemRenderer {
     pub title: Text,
     pub subtitle: Option<Text>,
     pub on_tap: Option<NavigationEndpoint>,
+    pub thumbnail_renderer: Option<ThumbnailRenderer>,
 }
 impl MusicTwoRowItemRenderer {
+    pub fn get_thumbnail_url(&self) -> Option<String> {
+        // thumbnails are ordered by size, the largest last
+        Some(
+            self.thumbnail_renderer
+                .as_ref()?
+                .music_thumbnail_renderer
+                .thumbnail
+                .thumbnails
+                .last()?
+                .url
+                .clone(),
+        )
+    }
+
     pub fn get_id(&self) -> Option<String> {
         self.title.runs.as_ref()?.first()?.get_id()
     }
@@ -327,6 +358,26 @@ impl MusicTwoRowItemRenderer {
 }
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
+pub struct ThumbnailRenderer {
+    pub music_thumbnail_renderer: MusicThumbnailRenderer,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MusicThumbnailRenderer {
+    pub thumbnail: Thumbnails,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Thumbnails {
+    pub thumbnails: Vec<Thumbnail>,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Thumbnail {
+    pub url: String,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct ContinuationItemRenderer {
     pub continuation_endpoint: ContinuationEndpoint,
 }
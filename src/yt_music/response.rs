@@ -4,12 +4,18 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use super::YtMusicApi;
+use super::model::Run;
 use super::model::YtMusicResponse;
-use crate::music_api::{Album, Artist, MusicApiType, Playlist, Playlists, Song, Songs};
+use crate::music_api::{
+    Album, Albums, Artist, Artists, MusicApiType, Playlist, Playlists, Song, SongVersion, Songs,
+};
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct SearchSongs(pub Vec<Song>);
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SearchAlbums(pub Vec<Album>);
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct SearchSongUnique(pub Option<Song>);
 
@@ -42,9 +48,13 @@ impl TryInto<Playlists> for YtMusicResponse {
                 .ok_or(eyre!("No playlist name"))?
                 .trim()
                 .to_string();
+            // NOTE: the description and visibility are not part of the library listing
             let playlist = Playlist {
                 id,
                 name,
+                description: None,
+                public: None,
+                cover_url: mtrir.get_thumbnail_url(),
                 songs: vec![],
             };
             playlists.push(playlist);
@@ -55,6 +65,106 @@ impl TryInto<Playlists> for YtMusicResponse {
     }
 }
 
+impl TryInto<Albums> for YtMusicResponse {
+    type Error = Error;
+
+    fn try_into(mut self) -> Result<Albums, Self::Error> {
+        let mut albums = vec![];
+        let Some(mtrirs) = self.get_mtrirs() else {
+            return Ok(Albums(albums));
+        };
+        for mtrir in mtrirs {
+            let id = mtrir.get_id().ok_or(eyre!("No album id"))?;
+            let name = mtrir.get_name().ok_or(eyre!("No album name"))?;
+            // subtitle = type, artists, year
+            let artists = mtrir
+                .subtitle
+                .as_ref()
+                .and_then(|s| s.runs.as_ref())
+                .map(|runs| parse_album_artists(runs))
+                .unwrap_or_default();
+            albums.push(Album {
+                id: Some(id),
+                name,
+                artists,
+                upc: None,
+            });
+        }
+        Ok(Albums(albums))
+    }
+}
+
+impl TryInto<SearchAlbums> for YtMusicResponse {
+    type Error = Error;
+
+    fn try_into(mut self) -> Result<SearchAlbums, Self::Error> {
+        let mut albums = vec![];
+        let Some(mrlirs) = self.get_mrlirs() else {
+            return Ok(SearchAlbums(albums));
+        };
+
+        // fc0 = album title
+        // fc1 = type, artists, year
+        for mrlir in mrlirs {
+            let Some(id) = mrlir.get_browse_id() else {
+                debug!("skipping album without browse id");
+                continue;
+            };
+            let name = mrlir.get_col_run_text(0, 0, true).ok_or(eyre!("No name"))?;
+            let artists = mrlir
+                .get_col_runs(1, true)
+                .map(|runs| parse_album_artists(runs))
+                .unwrap_or_default();
+            albums.push(Album {
+                id: Some(id),
+                name,
+                artists,
+                upc: None,
+            });
+        }
+        Ok(SearchAlbums(albums))
+    }
+}
+
+impl TryInto<Artists> for YtMusicResponse {
+    type Error = Error;
+
+    fn try_into(mut self) -> Result<Artists, Self::Error> {
+        let mut artists = vec![];
+        let Some(mrlirs) = self.get_mrlirs() else {
+            return Ok(Artists(artists));
+        };
+
+        // fc0 = artist name
+        // fc1 = type, subscribers
+        for mrlir in mrlirs {
+            let Some(id) = mrlir.get_browse_id() else {
+                debug!("skipping artist without browse id");
+                continue;
+            };
+            let name = mrlir.get_col_run_text(0, 0, true).ok_or(eyre!("No name"))?;
+            artists.push(Artist { id: Some(id), name });
+        }
+        Ok(Artists(artists))
+    }
+}
+
+fn parse_album_artists(runs: &[Run]) -> Vec<Artist> {
+    runs.iter()
+        .filter_map(|run| {
+            let id = run.get_id()?;
+            // artists and channels browse ids
+            if !id.starts_with("UC") {
+                return None;
+            }
+            Some(Artist {
+                id: Some(id),
+                name: run.get_text(),
+            })
+        })
+        .collect()
+}
+
 impl TryInto<Songs> for YtMusicResponse {
     type Error = Error;
 
@@ -87,6 +197,8 @@ impl TryInto<Songs> for YtMusicResponse {
                 Some(Album {
                     id: mrlir.get_col_run_id(2, 0, true),
                     name: mrlir.get_col_run_text(2, 0, true)?,
+                    artists: vec![],
+                    upc: None,
                 })
             });
             let mut artists: Vec<Artist> = vec![];
@@ -101,6 +213,7 @@ impl TryInto<Songs> for YtMusicResponse {
                     id: run.get_id(),
                 });
             }
+            let versions = SongVersion::parse(&name);
             let song = Song {
                 source: MusicApiType::YtMusic,
                 id,
@@ -110,6 +223,9 @@ impl TryInto<Songs> for YtMusicResponse {
                 artists,
                 album,
                 duration_ms: duration,
+                versions,
+                explicit: None,
+                added_at: None,
             };
 
             songs_vec.push(song);
@@ -164,6 +280,8 @@ impl TryInto<SearchSongs> for YtMusicResponse {
                         album = Some(Album {
                             id: Some(id),
                             name: text,
+                            artists: vec![],
+                            upc: None,
                         });
                     } else {
                         artists.push(Artist {
@@ -185,6 +303,7 @@ impl TryInto<SearchSongs> for YtMusicResponse {
                 debug!("skipping song with missing data: {}", name);
                 continue;
             }
+            let versions = SongVersion::parse(&name);
             let song = Song {
                 source: MusicApiType::YtMusic,
                 id,
@@ -194,6 +313,9 @@ impl TryInto<SearchSongs> for YtMusicResponse {
                 artists,
                 album,
                 duration_ms: duration,
+                versions,
+                explicit: None,
+                added_at: None,
             };
 
             songs_vec.push(song);
@@ -247,6 +369,8 @@ impl TryInto<SearchSongUnique> for YtMusicResponse {
                     album = Some(Album {
                         id: Some(id),
                         name: text,
+                        artists: vec![],
+                        upc: None,
                     });
                 } else {
                     artists.push(Artist {
@@ -272,6 +396,7 @@ impl TryInto<SearchSongUnique> for YtMusicResponse {
         //    return Ok(SearchSongUnique(None));
         //}
 
+        let versions = SongVersion::parse(&name);
         let song = Song {
             source: MusicApiType::YtMusic,
             id,
@@ -281,6 +406,9 @@ impl TryInto<SearchSongUnique> for YtMusicResponse {
             artists,
             album,
             duration_ms: duration,
+            versions,
+            explicit: None,
+            added_at: None,
         };
         Ok(SearchSongUnique(Some(song)))
     }